@@ -0,0 +1,338 @@
+use crate::error::Error;
+use crate::extractor::Extractor;
+use crate::header::Magic;
+use crate::macho::MachO;
+use crate::slice::SliceReader;
+use std::convert::TryInto;
+use std::io::{Read, Seek, SeekFrom};
+
+const ARCHIVE_MAGIC: &[u8; 8] = b"!<arch>\n";
+const MEMBER_HEADER_SIZE: u64 = 60;
+const MEMBER_HEADER_TERMINATOR: &[u8; 2] = b"`\n";
+const LONG_NAME_PREFIX: &str = "#1/";
+
+#[derive(PartialEq, Debug)]
+pub struct ArchiveMember {
+    pub name: String,
+    pub date: u64,
+    pub uid: u32,
+    pub gid: u32,
+    pub mode: u32,
+    pub header_offset: u64,
+    pub offset: u64,
+    pub size: u64,
+}
+
+impl ArchiveMember {
+    fn from_reader<R: Read + Seek>(r: &mut R, header_offset: u64) -> Result<Self, Error> {
+        let mut header = [0u8; MEMBER_HEADER_SIZE as usize];
+        r.read_exact(&mut header)?;
+        if &header[58..60] != MEMBER_HEADER_TERMINATOR {
+            return Err(Error::InvalidArchiveHeader(header_offset));
+        }
+        let field = |range: std::ops::Range<usize>| {
+            std::str::from_utf8(&header[range])
+                .map(|s| s.trim_end_matches(' '))
+                .map_err(|_| Error::InvalidArchiveHeader(header_offset))
+        };
+        let number = |range: std::ops::Range<usize>, radix: u32| {
+            let text = field(range)?;
+            if text.is_empty() {
+                Ok(0)
+            } else {
+                u64::from_str_radix(text, radix)
+                    .map_err(|_| Error::InvalidArchiveHeader(header_offset))
+            }
+        };
+
+        let name = field(0..16)?;
+        let date = number(16..28, 10)?;
+        let uid = number(28..34, 10)? as u32;
+        let gid = number(34..40, 10)? as u32;
+        let mode = number(40..48, 8)? as u32;
+        let mut size = number(48..58, 10)?;
+        let mut offset = header_offset + MEMBER_HEADER_SIZE;
+
+        let name = if let Some(length) = name.strip_prefix(LONG_NAME_PREFIX) {
+            let length: u64 = length
+                .parse()
+                .map_err(|_| Error::InvalidArchiveHeader(header_offset))?;
+            if length > size {
+                return Err(Error::InvalidArchiveHeader(header_offset));
+            }
+            let mut data = vec![0u8; length as usize];
+            r.read_exact(&mut data)?;
+            offset += length;
+            size -= length;
+            String::from_utf8(data.into_iter().take_while(|x| *x != 0).collect())?
+        } else {
+            name.to_string()
+        };
+
+        Ok(Self {
+            name,
+            date,
+            uid,
+            gid,
+            mode,
+            header_offset,
+            offset,
+            size,
+        })
+    }
+
+    pub fn is_symbol_table(&self) -> bool {
+        self.name.starts_with("__.SYMDEF")
+    }
+
+    // whether the member starts with a Mach-O header, rather than being some other file
+    pub fn is_object<R: Read + Seek>(&self, r: &mut R) -> Result<bool, Error> {
+        if self.size < 4 {
+            return Ok(false);
+        }
+        let mut magic = [0u8; 4];
+        self.reader(r)?.read_exact(&mut magic)?;
+        Ok(Magic::from_u32(u32::from_le_bytes(magic)).is_ok())
+    }
+
+    pub fn reader<'a, R: Read + Seek>(&self, r: &'a mut R) -> Result<SliceReader<'a, R>, Error> {
+        Ok(SliceReader::new(r, self.offset, self.size)?)
+    }
+
+    pub fn parse<R: Read + Seek>(&self, r: &mut R) -> Result<MachO, Error> {
+        MachO::from_reader(&mut self.reader(r)?)
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ArchiveSymbol {
+    pub name: String,
+    pub member_offset: u64,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Archive {
+    pub members: Vec<ArchiveMember>,
+    pub symbols: Vec<ArchiveSymbol>,
+}
+
+impl Archive {
+    pub fn from_reader<R: Read + Seek>(r: &mut R) -> Result<Self, Error> {
+        let start = r.stream_position()?;
+        let end = r.seek(SeekFrom::End(0))?;
+        r.seek(SeekFrom::Start(start))?;
+
+        let mut magic = [0u8; 8];
+        r.read_exact(&mut magic)?;
+        if &magic != ARCHIVE_MAGIC {
+            return Err(Error::InvalidArchiveMagic(magic.to_vec()));
+        }
+
+        let mut members = Vec::new();
+        let mut symbols = Vec::new();
+        let mut header_offset = start + magic.len() as u64;
+        while header_offset + MEMBER_HEADER_SIZE <= end {
+            r.seek(SeekFrom::Start(header_offset))?;
+            let member = ArchiveMember::from_reader(r, header_offset)?;
+            let next = member.offset + member.size;
+            if next > end {
+                return Err(Error::InvalidArchiveHeader(header_offset));
+            }
+            if member.is_symbol_table() && members.is_empty() && symbols.is_empty() {
+                symbols = Self::parse_symbol_table(&member, r, start)?;
+            } else {
+                members.push(member);
+            }
+            // members are padded to an even offset from the start of the archive
+            header_offset = next + ((next - start) % 2);
+        }
+        Ok(Self { members, symbols })
+    }
+
+    fn parse_symbol_table<R: Read + Seek>(
+        member: &ArchiveMember,
+        r: &mut R,
+        start: u64,
+    ) -> Result<Vec<ArchiveSymbol>, Error> {
+        let is_64_bit = member.name.starts_with("__.SYMDEF_64");
+        let word_size = if is_64_bit { 8 } else { 4 };
+
+        // The table is written in the byte order of the tool that created it, so guess based on
+        // which interpretation of the ranlib array size fits within the member.
+        let mut reader = member.reader(r)?;
+        let mut e = Extractor::little_endian(&mut reader);
+        let ranlib_size = Self::read_word(&mut e, is_64_bit)?;
+        let big_endian = ranlib_size
+            .checked_add(word_size)
+            .is_none_or(|size| size > member.size);
+        reader.seek(SeekFrom::Start(0))?;
+        let mut e = if big_endian {
+            Extractor::big_endian(&mut reader)
+        } else {
+            Extractor::little_endian(&mut reader)
+        };
+
+        let ranlib_size = Self::read_word(&mut e, is_64_bit)?;
+        let strings_offset = ranlib_size
+            .checked_add(2 * word_size)
+            .filter(|offset| *offset <= member.size)
+            .ok_or(Error::InvalidArchiveHeader(member.header_offset))?;
+        let mut entries = Vec::new();
+        for _ in 0..ranlib_size / (2 * word_size) {
+            let strx = Self::read_word(&mut e, is_64_bit)?;
+            let offset = Self::read_word(&mut e, is_64_bit)?;
+            entries.push((strx, offset));
+        }
+        let strsize = Self::read_word(&mut e, is_64_bit)?;
+        if strsize > member.size - strings_offset {
+            return Err(Error::InvalidArchiveHeader(member.header_offset));
+        }
+        let mut strings = vec![0u8; strsize as usize];
+        e.reader.read_exact(&mut strings)?;
+
+        entries
+            .into_iter()
+            .map(|(strx, offset)| {
+                let name = strings
+                    .get(strx as usize..)
+                    .ok_or(Error::InvalidArchiveHeader(member.header_offset))?;
+                Ok(ArchiveSymbol {
+                    name: String::from_utf8(
+                        name.iter().copied().take_while(|x| *x != 0).collect(),
+                    )?,
+                    member_offset: start + offset,
+                })
+            })
+            .collect()
+    }
+
+    fn read_word(e: &mut Extractor, is_64_bit: bool) -> Result<u64, Error> {
+        Ok(if is_64_bit {
+            e.try_into()?
+        } else {
+            let value: u32 = e.try_into()?;
+            value as u64
+        })
+    }
+
+    pub fn member_for_symbol(&self, name: &str) -> Option<&ArchiveMember> {
+        let symbol = self.symbols.iter().find(|symbol| symbol.name == name)?;
+        self.members
+            .iter()
+            .find(|member| member.header_offset == symbol.member_offset)
+    }
+
+    // skips symbol tables and members that aren't Mach-O objects
+    pub fn parse_all<R: Read + Seek>(&self, r: &mut R) -> Result<Vec<MachO>, Error> {
+        let mut objects = Vec::new();
+        for member in &self.members {
+            if !member.is_symbol_table() && member.is_object(r)? {
+                objects.push(member.parse(r)?);
+            }
+        }
+        Ok(objects)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn member(name: &str, data: &[u8]) -> Vec<u8> {
+        let mut member = format!(
+            "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+            name,
+            0,
+            0,
+            0,
+            644,
+            data.len()
+        )
+        .into_bytes();
+        member.extend_from_slice(data);
+        if !data.len().is_multiple_of(2) {
+            member.push(b'\n');
+        }
+        member
+    }
+
+    fn archive(members: &[Vec<u8>]) -> Vec<u8> {
+        let mut archive = ARCHIVE_MAGIC.to_vec();
+        for member in members {
+            archive.extend_from_slice(member);
+        }
+        archive
+    }
+
+    #[test]
+    fn symbol_table() {
+        let mut table = Vec::new();
+        table.extend_from_slice(&8u32.to_le_bytes());
+        table.extend_from_slice(&0u32.to_le_bytes());
+        table.extend_from_slice(&90u32.to_le_bytes());
+        table.extend_from_slice(&6u32.to_le_bytes());
+        table.extend_from_slice(b"_main\0");
+        let data = archive(&[member("__.SYMDEF", &table), member("a.o", b"abc")]);
+        let archive = Archive::from_reader(&mut Cursor::new(data)).unwrap();
+        assert_eq!(archive.members.len(), 1);
+        assert_eq!(archive.members[0].name, "a.o");
+        assert_eq!(archive.symbols[0].name, "_main");
+        assert_eq!(
+            archive.member_for_symbol("_main"),
+            Some(&archive.members[0])
+        );
+    }
+
+    #[test]
+    fn oversized_symbol_table() {
+        let mut table = Vec::new();
+        table.extend_from_slice(&u64::MAX.to_le_bytes());
+        table.extend_from_slice(&0u64.to_le_bytes());
+        let data = archive(&[member("__.SYMDEF_64", &table)]);
+        assert!(matches!(
+            Archive::from_reader(&mut Cursor::new(data)),
+            Err(Error::InvalidArchiveHeader(8))
+        ));
+
+        let mut table = Vec::new();
+        table.extend_from_slice(&0u32.to_le_bytes());
+        table.extend_from_slice(&0xffff_fff0u32.to_le_bytes());
+        let data = archive(&[member("__.SYMDEF", &table)]);
+        assert!(matches!(
+            Archive::from_reader(&mut Cursor::new(data)),
+            Err(Error::InvalidArchiveHeader(8))
+        ));
+    }
+
+    #[test]
+    fn padding_is_relative_to_archive_start() {
+        let mut data = vec![0u8];
+        data.extend(archive(&[member("a.o", b"abc"), member("b.o", b"de")]));
+        let mut r = Cursor::new(data);
+        r.seek(SeekFrom::Start(1)).unwrap();
+        let archive = Archive::from_reader(&mut r).unwrap();
+        let names: Vec<_> = archive.members.iter().map(|m| m.name.as_str()).collect();
+        assert_eq!(names, ["a.o", "b.o"]);
+        assert_eq!(archive.members[1].size, 2);
+    }
+
+    #[test]
+    fn parse_all_skips_other_members() {
+        let object = include_bytes!("../tests/fixtures/osx_launcher_x86_64");
+        let data = archive(&[
+            member("README", b"not an object\n"),
+            member("a.o", object),
+            member("x", b"x"),
+            member("__.SYMDEF SORTED", &[0; 8]),
+        ]);
+        let mut r = Cursor::new(data);
+        let archive = Archive::from_reader(&mut r).unwrap();
+        assert_eq!(archive.members.len(), 4);
+        let objects = archive.parse_all(&mut r).unwrap();
+        assert_eq!(
+            objects,
+            [MachO::from_reader(&mut Cursor::new(&object[..])).unwrap()]
+        );
+    }
+}
@@ -1,13 +1,33 @@
-use mach_o_man::{Error, MachO};
+use mach_o_man::{Archive, Error, FatBinary, MachO};
 use std::fs::File;
-use std::io::BufReader;
+use std::io::{BufReader, Seek, SeekFrom};
 
 fn main() -> Result<(), Error> {
     for file in std::env::args().skip(1) {
         let f = File::open(&file)?;
         let mut reader = BufReader::new(f);
-        let object = MachO::from_reader(&mut reader);
-        println!("{}:\n{:#x?}", file, object);
+        if let Ok(archive) = Archive::from_reader(&mut reader) {
+            for member in &archive.members {
+                let object = member.parse(&mut reader);
+                println!("{}({}):\n{:#x?}", file, member.name, object);
+            }
+            continue;
+        }
+        reader.seek(SeekFrom::Start(0))?;
+        match FatBinary::from_reader(&mut reader) {
+            Ok(fat) => {
+                for arch in &fat.arches {
+                    let object = arch.parse(&mut reader);
+                    println!("{} ({:?}):\n{:#x?}", file, arch.cputype, object);
+                }
+            }
+            Err(Error::InvalidMagic(_)) => {
+                reader.seek(SeekFrom::Start(0))?;
+                let object = MachO::from_reader(&mut reader);
+                println!("{}:\n{:#x?}", file, object);
+            }
+            Err(error) => return Err(error),
+        }
     }
     Ok(())
 }
@@ -0,0 +1,696 @@
+use crate::command::{LoadCommand, SegmentInfo};
+use crate::constants;
+use crate::error::Error;
+use crate::extractor::{c_string, read_at};
+use crate::header::Header;
+use crate::macho::MachO;
+use crate::symbol::LibraryOrdinal;
+use crate::{extract, extractable};
+use std::convert::TryInto;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+extractable!(ChainedFixupsHeader {
+    fixups_version: u32,
+    starts_offset: u32,
+    imports_offset: u32,
+    symbols_offset: u32,
+    imports_count: u32,
+    imports_format: u32,
+    symbols_format: u32,
+});
+
+extractable!(ChainedStartsInSegment {
+    size: u32,
+    page_size: u16,
+    pointer_format: u16,
+    segment_offset: u64,
+    max_valid_pointer: u32,
+    page_count: u16,
+    page_start: Vec<u16> = Vec::new(),
+});
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ChainedPointerFormat {
+    Arm64e,
+    Ptr64,
+    Ptr32,
+    Ptr32Cache,
+    Ptr32Firmware,
+    Ptr64Offset,
+    Arm64eKernel,
+    Ptr64KernelCache,
+    Arm64eUserland,
+    Arm64eFirmware,
+    X86_64KernelCache,
+    Arm64eUserland24,
+    Arm64eSharedCache,
+    Arm64eSegmented,
+    Unknown(u16),
+}
+
+impl ChainedPointerFormat {
+    fn from_u16(v: u16) -> Self {
+        match v {
+            constants::DYLD_CHAINED_PTR_ARM64E => Self::Arm64e,
+            constants::DYLD_CHAINED_PTR_64 => Self::Ptr64,
+            constants::DYLD_CHAINED_PTR_32 => Self::Ptr32,
+            constants::DYLD_CHAINED_PTR_32_CACHE => Self::Ptr32Cache,
+            constants::DYLD_CHAINED_PTR_32_FIRMWARE => Self::Ptr32Firmware,
+            constants::DYLD_CHAINED_PTR_64_OFFSET => Self::Ptr64Offset,
+            constants::DYLD_CHAINED_PTR_ARM64E_KERNEL => Self::Arm64eKernel,
+            constants::DYLD_CHAINED_PTR_64_KERNEL_CACHE => Self::Ptr64KernelCache,
+            constants::DYLD_CHAINED_PTR_ARM64E_USERLAND => Self::Arm64eUserland,
+            constants::DYLD_CHAINED_PTR_ARM64E_FIRMWARE => Self::Arm64eFirmware,
+            constants::DYLD_CHAINED_PTR_X86_64_KERNEL_CACHE => Self::X86_64KernelCache,
+            constants::DYLD_CHAINED_PTR_ARM64E_USERLAND24 => Self::Arm64eUserland24,
+            constants::DYLD_CHAINED_PTR_ARM64E_SHARED_CACHE => Self::Arm64eSharedCache,
+            constants::DYLD_CHAINED_PTR_ARM64E_SEGMENTED => Self::Arm64eSegmented,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+
+    pub fn is_32_bit(&self) -> bool {
+        matches!(self, Self::Ptr32 | Self::Ptr32Cache | Self::Ptr32Firmware)
+    }
+
+    // distance in bytes represented by each unit of a pointer's `next` field
+    pub fn stride(&self) -> Option<u64> {
+        match self {
+            Self::Arm64e | Self::Arm64eUserland | Self::Arm64eUserland24 => Some(8),
+            Self::Arm64eSharedCache => Some(8),
+            Self::Arm64eKernel | Self::Arm64eFirmware | Self::Arm64eSegmented => Some(4),
+            Self::Ptr64 | Self::Ptr64Offset | Self::Ptr64KernelCache => Some(4),
+            Self::Ptr32 | Self::Ptr32Cache | Self::Ptr32Firmware => Some(4),
+            Self::X86_64KernelCache => Some(1),
+            Self::Unknown(_) => None,
+        }
+    }
+}
+
+impl ChainedStartsInSegment {
+    pub fn format(&self) -> ChainedPointerFormat {
+        ChainedPointerFormat::from_u16(self.pointer_format)
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ChainedImport {
+    pub library_ordinal: LibraryOrdinal,
+    pub weak_import: bool,
+    pub name: String,
+    pub addend: i64,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct PointerAuth {
+    // 0 = IA, 1 = IB, 2 = DA, 3 = DB
+    pub key: u8,
+    pub address_diversity: bool,
+    pub diversity: u16,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ChainedFixupKind {
+    // the target is an unslid vm address, including any high byte
+    Rebase {
+        target: u64,
+    },
+    Bind {
+        import_index: u32,
+        symbol_name: String,
+        library_ordinal: LibraryOrdinal,
+        weak_import: bool,
+        addend: i64,
+    },
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ChainedFixup {
+    pub segment_index: u32,
+    pub address: u64,
+    pub file_offset: u64,
+    pub kind: ChainedFixupKind,
+    pub auth: Option<PointerAuth>,
+}
+
+fn bits(value: u64, start: u32, width: u32) -> u64 {
+    (value >> start) & ((1 << width) - 1)
+}
+
+fn sign_extend(value: u64, width: u32) -> i64 {
+    let shift = 64 - width;
+    ((value << shift) as i64) >> shift
+}
+
+fn library_ordinal(ordinal: u64, width: u32) -> LibraryOrdinal {
+    // special ordinals are stored as small negative numbers
+    match sign_extend(ordinal, width) {
+        0 => LibraryOrdinal::SelfImage,
+        -1 => LibraryOrdinal::MainExecutable,
+        -2 => LibraryOrdinal::FlatLookup,
+        -3 => LibraryOrdinal::WeakLookup,
+        _ => LibraryOrdinal::Dylib(ordinal as u16),
+    }
+}
+
+enum Pointer {
+    Rebase { target: u64 },
+    Bind { ordinal: u32, addend: i64 },
+    Value,
+}
+
+struct ChainContext<'a> {
+    format: ChainedPointerFormat,
+    image_base: u64,
+    max_valid_pointer: u32,
+    segments: &'a [SegmentInfo<'a>],
+}
+
+impl ChainContext<'_> {
+    fn arm64e_auth(raw: u64) -> PointerAuth {
+        PointerAuth {
+            key: bits(raw, 49, 2) as u8,
+            address_diversity: bits(raw, 48, 1) != 0,
+            diversity: bits(raw, 32, 16) as u16,
+        }
+    }
+
+    // returns the `next` field along with the decoded pointer
+    fn decode(&self, raw: u64) -> Result<(u64, Pointer, Option<PointerAuth>), Error> {
+        use ChainedPointerFormat::*;
+        Ok(match self.format {
+            Ptr64 | Ptr64Offset => {
+                let next = bits(raw, 51, 12);
+                if bits(raw, 63, 1) != 0 {
+                    let ordinal = bits(raw, 0, 24) as u32;
+                    let addend = bits(raw, 24, 8) as i64;
+                    (next, Pointer::Bind { ordinal, addend }, None)
+                } else {
+                    let mut target = bits(raw, 0, 36);
+                    if self.format == Ptr64Offset {
+                        target += self.image_base;
+                    }
+                    let target = target | bits(raw, 36, 8) << 56;
+                    (next, Pointer::Rebase { target }, None)
+                }
+            }
+            Arm64e | Arm64eKernel | Arm64eUserland | Arm64eFirmware | Arm64eUserland24 => {
+                let next = bits(raw, 51, 11);
+                let is_auth = bits(raw, 63, 1) != 0;
+                let is_bind = bits(raw, 62, 1) != 0;
+                let auth = if is_auth {
+                    Some(Self::arm64e_auth(raw))
+                } else {
+                    None
+                };
+                if is_bind {
+                    let ordinal_width = if self.format == Arm64eUserland24 {
+                        24
+                    } else {
+                        16
+                    };
+                    let ordinal = bits(raw, 0, ordinal_width) as u32;
+                    let addend = if is_auth {
+                        0
+                    } else {
+                        sign_extend(bits(raw, 32, 19), 19)
+                    };
+                    (next, Pointer::Bind { ordinal, addend }, auth)
+                } else if is_auth {
+                    let target = self.image_base + bits(raw, 0, 32);
+                    (next, Pointer::Rebase { target }, auth)
+                } else {
+                    let mut target = bits(raw, 0, 43);
+                    if matches!(
+                        self.format,
+                        Arm64eKernel | Arm64eUserland | Arm64eUserland24
+                    ) {
+                        target += self.image_base;
+                    }
+                    let target = target | bits(raw, 43, 8) << 56;
+                    (next, Pointer::Rebase { target }, None)
+                }
+            }
+            Ptr64KernelCache | X86_64KernelCache => {
+                let next = bits(raw, 51, 12);
+                let auth = if bits(raw, 63, 1) != 0 {
+                    Some(Self::arm64e_auth(raw))
+                } else {
+                    None
+                };
+                let target = self.image_base + bits(raw, 0, 30);
+                (next, Pointer::Rebase { target }, auth)
+            }
+            Arm64eSharedCache => {
+                let next = bits(raw, 52, 11);
+                let target = self.image_base + bits(raw, 0, 34);
+                if bits(raw, 63, 1) != 0 {
+                    let auth = PointerAuth {
+                        key: if bits(raw, 51, 1) != 0 { 2 } else { 0 },
+                        address_diversity: bits(raw, 50, 1) != 0,
+                        diversity: bits(raw, 34, 16) as u16,
+                    };
+                    (next, Pointer::Rebase { target }, Some(auth))
+                } else {
+                    let target = target | bits(raw, 34, 8) << 56;
+                    (next, Pointer::Rebase { target }, None)
+                }
+            }
+            Arm64eSegmented => {
+                let next = bits(raw, 51, 12);
+                let index = bits(raw, 28, 4) as u32;
+                let segment = self
+                    .segments
+                    .get(index as usize)
+                    .ok_or(Error::InvalidSegmentIndex(index))?;
+                let target = segment.vmaddr + bits(raw, 0, 28);
+                let auth = if bits(raw, 63, 1) != 0 {
+                    Some(Self::arm64e_auth(raw))
+                } else {
+                    None
+                };
+                (next, Pointer::Rebase { target }, auth)
+            }
+            Ptr32 => {
+                let next = bits(raw, 26, 5);
+                if bits(raw, 31, 1) != 0 {
+                    let ordinal = bits(raw, 0, 20) as u32;
+                    let addend = bits(raw, 20, 6) as i64;
+                    (next, Pointer::Bind { ordinal, addend }, None)
+                } else {
+                    let target = bits(raw, 0, 26);
+                    // values above the maximum are non-pointer data stored in the chain
+                    if target > self.max_valid_pointer as u64 {
+                        (next, Pointer::Value, None)
+                    } else {
+                        (next, Pointer::Rebase { target }, None)
+                    }
+                }
+            }
+            Ptr32Cache => {
+                let target = self.image_base + bits(raw, 0, 30);
+                (bits(raw, 30, 2), Pointer::Rebase { target }, None)
+            }
+            Ptr32Firmware => {
+                let target = bits(raw, 0, 26);
+                (bits(raw, 26, 6), Pointer::Rebase { target }, None)
+            }
+            Unknown(format) => return Err(Error::UnsupportedPointerFormat(format)),
+        })
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ChainedFixups {
+    pub header: ChainedFixupsHeader,
+    pub segments: Vec<Option<ChainedStartsInSegment>>,
+    pub imports: Vec<ChainedImport>,
+}
+
+impl ChainedFixups {
+    pub fn parse(header: &Header, data: &[u8]) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(data);
+        let mut e = header.magic.get_extractor(&mut cursor);
+        let fixups_header: ChainedFixupsHeader = extract!(e);
+
+        // starts_in_image, followed by the per-segment tables it points to
+        e.reader
+            .seek(SeekFrom::Start(fixups_header.starts_offset as u64))?;
+        let seg_count: u32 = extract!(e);
+        let mut seg_info_offsets = Vec::new();
+        for _ in 0..seg_count {
+            let offset: u32 = extract!(e);
+            seg_info_offsets.push(offset);
+        }
+        let mut segments = Vec::new();
+        for seg_info_offset in seg_info_offsets {
+            if seg_info_offset == 0 {
+                segments.push(None);
+                continue;
+            }
+            e.reader.seek(SeekFrom::Start(
+                fixups_header.starts_offset as u64 + seg_info_offset as u64,
+            ))?;
+            let mut starts: ChainedStartsInSegment = extract!(e);
+            // 32-bit formats store overflow chain starts after the per-page entries
+            let entries =
+                std::cmp::max(starts.page_count as u32, starts.size.saturating_sub(22) / 2);
+            for _ in 0..entries {
+                starts.page_start.push(extract!(e));
+            }
+            segments.push(Some(starts));
+        }
+
+        if fixups_header.symbols_format != constants::DYLD_CHAINED_SYMBOL_UNCOMPRESSED {
+            return Err(Error::UnsupportedSymbolFormat(fixups_header.symbols_format));
+        }
+        let symbols =
+            data.get(fixups_header.symbols_offset as usize..)
+                .ok_or(Error::UnexpectedEndOfData(
+                    fixups_header.symbols_offset as u64,
+                ))?;
+        e.reader
+            .seek(SeekFrom::Start(fixups_header.imports_offset as u64))?;
+        let mut imports = Vec::new();
+        for _ in 0..fixups_header.imports_count {
+            let (ordinal, weak_import, name_offset, addend) = match fixups_header.imports_format {
+                constants::DYLD_CHAINED_IMPORT | constants::DYLD_CHAINED_IMPORT_ADDEND => {
+                    let import: u32 = extract!(e);
+                    let import = import as u64;
+                    let addend =
+                        if fixups_header.imports_format == constants::DYLD_CHAINED_IMPORT_ADDEND {
+                            let addend: u32 = extract!(e);
+                            addend as i32 as i64
+                        } else {
+                            0
+                        };
+                    (
+                        library_ordinal(bits(import, 0, 8), 8),
+                        bits(import, 8, 1) != 0,
+                        bits(import, 9, 23),
+                        addend,
+                    )
+                }
+                constants::DYLD_CHAINED_IMPORT_ADDEND64 => {
+                    let import: u64 = extract!(e);
+                    let addend: u64 = extract!(e);
+                    (
+                        library_ordinal(bits(import, 0, 16), 16),
+                        bits(import, 16, 1) != 0,
+                        bits(import, 32, 32),
+                        addend as i64,
+                    )
+                }
+                format => return Err(Error::UnsupportedImportFormat(format)),
+            };
+            let name = symbols
+                .get(name_offset as usize..)
+                .ok_or(Error::InvalidStringIndex(name_offset as u32))?;
+            imports.push(ChainedImport {
+                library_ordinal: ordinal,
+                weak_import,
+                name: c_string(name)?,
+                addend,
+            });
+        }
+
+        Ok(Self {
+            header: fixups_header,
+            segments,
+            imports,
+        })
+    }
+
+    pub fn fixups<R: Read + Seek>(
+        &self,
+        macho: &MachO,
+        r: &mut R,
+    ) -> Result<Vec<ChainedFixup>, Error> {
+        let segment_infos = macho.segments();
+        let image_base = segment_infos
+            .iter()
+            .find(|segment| segment.fileoff == 0 && segment.filesize != 0)
+            .map_or(0, |segment| segment.vmaddr);
+
+        let mut fixups = Vec::new();
+        for (segment_index, starts) in self.segments.iter().enumerate() {
+            let starts = match starts {
+                Some(starts) => starts,
+                None => continue,
+            };
+            let segment = segment_infos
+                .get(segment_index)
+                .ok_or(Error::InvalidSegmentIndex(segment_index as u32))?;
+            let format = starts.format();
+            let stride = format
+                .stride()
+                .ok_or(Error::UnsupportedPointerFormat(starts.pointer_format))?;
+            let context = ChainContext {
+                format,
+                image_base,
+                max_valid_pointer: starts.max_valid_pointer,
+                segments: &segment_infos,
+            };
+
+            for page_index in 0..starts.page_count as usize {
+                let page_start = starts.page_start[page_index];
+                if page_start == constants::DYLD_CHAINED_PTR_START_NONE {
+                    continue;
+                }
+                let page_offset = page_index as u64 * starts.page_size as u64;
+                let mut chain_starts = Vec::new();
+                if format.is_32_bit() && page_start & constants::DYLD_CHAINED_PTR_START_MULTI != 0 {
+                    let mut index =
+                        (page_start & !constants::DYLD_CHAINED_PTR_START_MULTI) as usize;
+                    loop {
+                        let entry = *starts
+                            .page_start
+                            .get(index)
+                            .ok_or(Error::InvalidSegmentIndex(segment_index as u32))?;
+                        chain_starts.push(entry & !constants::DYLD_CHAINED_PTR_START_LAST);
+                        if entry & constants::DYLD_CHAINED_PTR_START_LAST != 0 {
+                            break;
+                        }
+                        index += 1;
+                    }
+                } else {
+                    chain_starts.push(page_start);
+                }
+
+                for chain_start in chain_starts {
+                    let mut offset = page_offset + chain_start as u64;
+                    loop {
+                        if offset >= segment.filesize {
+                            return Err(Error::UnexpectedEndOfData(segment.fileoff + offset));
+                        }
+                        let file_offset = segment.fileoff + offset;
+                        r.seek(SeekFrom::Start(file_offset))?;
+                        let mut e = macho.header.magic.get_extractor(r);
+                        let raw = if format.is_32_bit() {
+                            let raw: u32 = extract!(e);
+                            raw as u64
+                        } else {
+                            extract!(e)
+                        };
+                        let (next, pointer, auth) = context.decode(raw)?;
+                        let kind = match pointer {
+                            Pointer::Rebase { target } => Some(ChainedFixupKind::Rebase { target }),
+                            Pointer::Bind { ordinal, addend } => {
+                                let import = self
+                                    .imports
+                                    .get(ordinal as usize)
+                                    .ok_or(Error::InvalidSymbolIndex(ordinal))?;
+                                Some(ChainedFixupKind::Bind {
+                                    import_index: ordinal,
+                                    symbol_name: import.name.clone(),
+                                    library_ordinal: import.library_ordinal,
+                                    weak_import: import.weak_import,
+                                    addend: import.addend.wrapping_add(addend),
+                                })
+                            }
+                            Pointer::Value => None,
+                        };
+                        if let Some(kind) = kind {
+                            fixups.push(ChainedFixup {
+                                segment_index: segment_index as u32,
+                                address: segment.vmaddr + offset,
+                                file_offset,
+                                kind,
+                                auth,
+                            });
+                        }
+                        if next == 0 {
+                            break;
+                        }
+                        offset += next * stride;
+                    }
+                }
+            }
+        }
+        Ok(fixups)
+    }
+}
+
+impl MachO {
+    pub fn chained_fixups<R: Read + Seek>(
+        &self,
+        r: &mut R,
+    ) -> Result<Option<ChainedFixups>, Error> {
+        let command = self.commands.iter().find_map(|command| match command {
+            LoadCommand::DyldChainedFixups(command) => Some(command),
+            _ => None,
+        });
+        match command {
+            Some(command) => {
+                let data = read_at(r, command.dataoff as u64, command.datasize as u64)?;
+                Ok(Some(ChainedFixups::parse(&self.header, &data)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ChainedPointerFormat::*;
+
+    const IMAGE_BASE: u64 = 0x1_0000_0000;
+
+    #[derive(PartialEq, Debug)]
+    enum Decoded {
+        Rebase(u64),
+        Bind(u32, i64),
+        Value,
+    }
+
+    fn decode(format: ChainedPointerFormat, raw: u64) -> (u64, Decoded, Option<PointerAuth>) {
+        let segments = [SegmentInfo {
+            segname: "__DATA",
+            vmaddr: 0x2_0000_0000,
+            vmsize: 0x4000,
+            fileoff: 0x4000,
+            filesize: 0x4000,
+            maxprot: 3,
+            initprot: 3,
+            flags: 0,
+        }];
+        let context = ChainContext {
+            format,
+            image_base: IMAGE_BASE,
+            max_valid_pointer: 0x10_0000,
+            segments: &segments,
+        };
+        let (next, pointer, auth) = context.decode(raw).unwrap();
+        let pointer = match pointer {
+            Pointer::Rebase { target } => Decoded::Rebase(target),
+            Pointer::Bind { ordinal, addend } => Decoded::Bind(ordinal, addend),
+            Pointer::Value => Decoded::Value,
+        };
+        (next, pointer, auth)
+    }
+
+    fn auth(key: u8, address_diversity: bool, diversity: u16) -> Option<PointerAuth> {
+        Some(PointerAuth {
+            key,
+            address_diversity,
+            diversity,
+        })
+    }
+
+    #[test]
+    fn ptr64() {
+        let rebase = (3 << 51) | (0x80 << 36) | 0x1_0000_4000;
+        assert_eq!(
+            decode(Ptr64, rebase),
+            (3, Decoded::Rebase(0x8000_0001_0000_4000), None)
+        );
+        assert_eq!(
+            decode(Ptr64Offset, rebase),
+            (3, Decoded::Rebase(0x8000_0002_0000_4000), None)
+        );
+        let bind = (1 << 63) | (2 << 51) | (0x10 << 24) | 5;
+        assert_eq!(decode(Ptr64, bind), (2, Decoded::Bind(5, 0x10), None));
+        assert_eq!(decode(Ptr64Offset, bind), (2, Decoded::Bind(5, 0x10), None));
+    }
+
+    #[test]
+    fn arm64e() {
+        let rebase = (1 << 51) | (0x12 << 43) | 0x4000;
+        assert_eq!(
+            decode(Arm64e, rebase),
+            (1, Decoded::Rebase(0x1200_0000_0000_4000), None)
+        );
+        assert_eq!(
+            decode(Arm64eFirmware, rebase),
+            (1, Decoded::Rebase(0x1200_0000_0000_4000), None)
+        );
+        for format in [Arm64eKernel, Arm64eUserland, Arm64eUserland24] {
+            assert_eq!(
+                decode(format, rebase),
+                (1, Decoded::Rebase(0x1200_0001_0000_4000), None)
+            );
+        }
+
+        let auth_rebase = (1 << 63) | (4 << 51) | (2 << 49) | (1 << 48) | (0xbeef << 32) | 0x4000;
+        assert_eq!(
+            decode(Arm64e, auth_rebase),
+            (4, Decoded::Rebase(0x1_0000_4000), auth(2, true, 0xbeef))
+        );
+
+        let bind = (1 << 62) | (7 << 51) | (0x7_fff0 << 32) | 3;
+        assert_eq!(decode(Arm64e, bind), (7, Decoded::Bind(3, -0x10), None));
+
+        let auth_bind = (3 << 62) | (5 << 51) | (1 << 49) | (0x1234 << 32) | 0x12_0003;
+        assert_eq!(
+            decode(Arm64e, auth_bind),
+            (5, Decoded::Bind(3, 0), auth(1, false, 0x1234))
+        );
+        assert_eq!(
+            decode(Arm64eUserland24, auth_bind),
+            (5, Decoded::Bind(0x12_0003, 0), auth(1, false, 0x1234))
+        );
+    }
+
+    #[test]
+    fn kernel_cache() {
+        let raw = (1 << 63) | (6 << 51) | (3 << 49) | (0x55aa << 32) | 0x4000;
+        for format in [Ptr64KernelCache, X86_64KernelCache] {
+            assert_eq!(
+                decode(format, raw),
+                (6, Decoded::Rebase(0x1_0000_4000), auth(3, false, 0x55aa))
+            );
+        }
+        let raw = (6 << 51) | 0x4000;
+        assert_eq!(
+            decode(Ptr64KernelCache, raw),
+            (6, Decoded::Rebase(0x1_0000_4000), None)
+        );
+    }
+
+    #[test]
+    fn arm64e_shared_cache() {
+        let rebase = (9 << 52) | (0x34 << 34) | 0x4000;
+        assert_eq!(
+            decode(Arm64eSharedCache, rebase),
+            (9, Decoded::Rebase(0x3400_0001_0000_4000), None)
+        );
+        let auth_rebase = (1 << 63) | (9 << 52) | (1 << 51) | (1 << 50) | (0x4321 << 34) | 0x4000;
+        assert_eq!(
+            decode(Arm64eSharedCache, auth_rebase),
+            (9, Decoded::Rebase(0x1_0000_4000), auth(2, true, 0x4321))
+        );
+    }
+
+    #[test]
+    fn arm64e_segmented() {
+        let rebase = (2 << 51) | 0x123;
+        assert_eq!(
+            decode(Arm64eSegmented, rebase),
+            (2, Decoded::Rebase(0x2_0000_0123), None)
+        );
+        let auth_rebase = (1 << 63) | (2 << 51) | (1 << 49) | (1 << 48) | (0x77 << 32) | 0x123;
+        assert_eq!(
+            decode(Arm64eSegmented, auth_rebase),
+            (2, Decoded::Rebase(0x2_0000_0123), auth(1, true, 0x77))
+        );
+    }
+
+    #[test]
+    fn ptr32() {
+        let rebase = (4 << 26) | 0x4000;
+        assert_eq!(decode(Ptr32, rebase), (4, Decoded::Rebase(0x4000), None));
+        let value = (4 << 26) | 0x20_0000;
+        assert_eq!(decode(Ptr32, value), (4, Decoded::Value, None));
+        let bind = (1 << 31) | (4 << 26) | (0x3 << 20) | 7;
+        assert_eq!(decode(Ptr32, bind), (4, Decoded::Bind(7, 3), None));
+        assert_eq!(
+            decode(Ptr32Cache, (2 << 30) | 0x4000),
+            (2, Decoded::Rebase(0x1_0000_4000), None)
+        );
+        assert_eq!(
+            decode(Ptr32Firmware, (0x21 << 26) | 0x4000),
+            (0x21, Decoded::Rebase(0x4000), None)
+        );
+    }
+}
@@ -0,0 +1,301 @@
+use crate::code_signature::{Blob, CodeSignature};
+use crate::der::{self, DerObject, DerReader};
+use crate::entitlements::Value;
+use crate::error::Error;
+use crate::macho::MachO;
+use crate::plist;
+use std::io::{Read, Seek};
+
+const OID_SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const OID_SIGNING_TIME: &str = "1.2.840.113549.1.9.5";
+const OID_CDHASH_PLIST: &str = "1.2.840.113635.100.9.1";
+const OID_CDHASHES: &str = "1.2.840.113635.100.9.2";
+const OID_COMMON_NAME: &str = "2.5.4.3";
+const OID_COUNTRY: &str = "2.5.4.6";
+const OID_LOCALITY: &str = "2.5.4.7";
+const OID_STATE: &str = "2.5.4.8";
+const OID_ORGANIZATION: &str = "2.5.4.10";
+const OID_ORGANIZATIONAL_UNIT: &str = "2.5.4.11";
+const OID_USER_ID: &str = "0.9.2342.19200300.100.1.1";
+
+// relative distinguished names, in certificate order, as (oid, value)
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Name {
+    pub attributes: Vec<(String, String)>,
+}
+
+impl Name {
+    fn parse(object: DerObject) -> Result<Self, Error> {
+        let mut attributes = Vec::new();
+        let mut sets = object.expect(der::TAG_SEQUENCE)?.children();
+        while !sets.is_empty() {
+            let mut set = sets.read()?.expect(der::TAG_SET)?.children();
+            while !set.is_empty() {
+                let mut attribute = set.read()?.expect(der::TAG_SEQUENCE)?.children();
+                let oid = attribute.read()?.expect(der::TAG_OID)?.oid()?;
+                let value = attribute.read()?.string()?;
+                attributes.push((oid, value));
+            }
+        }
+        Ok(Self { attributes })
+    }
+
+    pub fn get(&self, oid: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(attribute, _)| attribute == oid)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn common_name(&self) -> Option<&str> {
+        self.get(OID_COMMON_NAME)
+    }
+
+    pub fn organizational_unit(&self) -> Option<&str> {
+        self.get(OID_ORGANIZATIONAL_UNIT)
+    }
+
+    pub fn organization(&self) -> Option<&str> {
+        self.get(OID_ORGANIZATION)
+    }
+}
+
+impl std::fmt::Display for Name {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for (index, (oid, value)) in self.attributes.iter().enumerate() {
+            if index != 0 {
+                write!(f, ", ")?;
+            }
+            let key = match oid.as_str() {
+                OID_COMMON_NAME => "CN",
+                OID_COUNTRY => "C",
+                OID_LOCALITY => "L",
+                OID_STATE => "ST",
+                OID_ORGANIZATION => "O",
+                OID_ORGANIZATIONAL_UNIT => "OU",
+                OID_USER_ID => "UID",
+                oid => oid,
+            };
+            write!(f, "{}={}", key, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Certificate {
+    pub serial: Vec<u8>,
+    pub issuer: Name,
+    pub subject: Name,
+    pub not_before: String,
+    pub not_after: String,
+}
+
+impl Certificate {
+    fn parse(object: DerObject) -> Result<Self, Error> {
+        let mut certificate = object.expect(der::TAG_SEQUENCE)?.children();
+        let mut tbs = certificate.read()?.expect(der::TAG_SEQUENCE)?.children();
+        let mut serial = tbs.read()?;
+        if serial.tag == der::TAG_CONTEXT_0 {
+            serial = tbs.read()?;
+        }
+        let serial = serial.expect(der::TAG_INTEGER)?.contents.to_vec();
+        tbs.read()?.expect(der::TAG_SEQUENCE)?;
+        let issuer = Name::parse(tbs.read()?)?;
+        let mut validity = tbs.read()?.expect(der::TAG_SEQUENCE)?.children();
+        let not_before = validity.read()?.time()?;
+        let not_after = validity.read()?.time()?;
+        let subject = Name::parse(tbs.read()?)?;
+        Ok(Self {
+            serial,
+            issuer,
+            subject,
+            not_before,
+            not_after,
+        })
+    }
+
+    // Apple developer certificates carry the team identifier as the subject's OU
+    pub fn team_id(&self) -> Option<&str> {
+        self.subject.organizational_unit()
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct CmsSignature {
+    // ordered from the signing certificate towards the root when the chain can be followed
+    pub certificates: Vec<Certificate>,
+    pub signing_time: Option<String>,
+    // CDHashes from the plist attribute, truncated to 20 bytes
+    pub cdhashes: Vec<Vec<u8>>,
+    // full CDHashes with their digest algorithm OIDs
+    pub cdhashes_by_algorithm: Vec<(String, Vec<u8>)>,
+}
+
+fn cdhash_plist(data: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+    let mut cdhashes = Vec::new();
+    if let Value::Dictionary(dictionary) = plist::parse(data)? {
+        if let Some(Value::Array(hashes)) = dictionary.get("cdhashes") {
+            for hash in hashes {
+                if let Value::Data(hash) = hash {
+                    cdhashes.push(hash.clone());
+                }
+            }
+        }
+    }
+    Ok(cdhashes)
+}
+
+impl CmsSignature {
+    pub fn parse(data: &[u8]) -> Result<Self, Error> {
+        let mut content_info = DerReader::new(data)
+            .read()?
+            .expect(der::TAG_SEQUENCE)?
+            .children();
+        let content_type = content_info.read()?.expect(der::TAG_OID)?;
+        if content_type.oid()? != OID_SIGNED_DATA {
+            return Err(Error::InvalidDer(content_type.offset as u64));
+        }
+        let mut signed_data = content_info
+            .read()?
+            .expect(der::TAG_CONTEXT_0)?
+            .children()
+            .read()?
+            .expect(der::TAG_SEQUENCE)?
+            .children();
+        signed_data.read()?.expect(der::TAG_INTEGER)?;
+        signed_data.read()?.expect(der::TAG_SET)?;
+        signed_data.read()?.expect(der::TAG_SEQUENCE)?;
+
+        let mut certificates = Vec::new();
+        let mut next = signed_data.read()?;
+        if next.tag == der::TAG_CONTEXT_0 {
+            let mut set = next.children();
+            while !set.is_empty() {
+                certificates.push(Certificate::parse(set.read()?)?);
+            }
+            next = signed_data.read()?;
+        }
+        if next.tag == der::TAG_CONTEXT_1 {
+            next = signed_data.read()?;
+        }
+
+        // only the first signer is inspected
+        let mut signer_infos = next.expect(der::TAG_SET)?.children();
+        let mut signing_time = None;
+        let mut cdhashes = Vec::new();
+        let mut cdhashes_by_algorithm = Vec::new();
+        let mut signer = None;
+        if !signer_infos.is_empty() {
+            let mut signer_info = signer_infos.read()?.expect(der::TAG_SEQUENCE)?.children();
+            signer_info.read()?.expect(der::TAG_INTEGER)?;
+            let identifier = signer_info.read()?;
+            if identifier.tag == der::TAG_SEQUENCE {
+                let mut issuer_and_serial = identifier.children();
+                let issuer = Name::parse(issuer_and_serial.read()?)?;
+                let serial = issuer_and_serial.read()?.expect(der::TAG_INTEGER)?;
+                signer = Some((issuer, serial.contents.to_vec()));
+            }
+            signer_info.read()?.expect(der::TAG_SEQUENCE)?;
+            let attributes = signer_info.read()?;
+            if attributes.tag == der::TAG_CONTEXT_0 {
+                let mut attributes = attributes.children();
+                while !attributes.is_empty() {
+                    let mut attribute = attributes.read()?.expect(der::TAG_SEQUENCE)?.children();
+                    let oid = attribute.read()?.expect(der::TAG_OID)?.oid()?;
+                    let mut values = attribute.read()?.expect(der::TAG_SET)?.children();
+                    while !values.is_empty() {
+                        let value = values.read()?;
+                        match oid.as_str() {
+                            OID_SIGNING_TIME => signing_time = Some(value.time()?),
+                            OID_CDHASH_PLIST => cdhashes.extend(cdhash_plist(
+                                value.expect(der::TAG_OCTET_STRING)?.contents,
+                            )?),
+                            OID_CDHASHES => {
+                                let mut hash = value.expect(der::TAG_SEQUENCE)?.children();
+                                let algorithm = hash.read()?.expect(der::TAG_OID)?.oid()?;
+                                let digest = hash.read()?.expect(der::TAG_OCTET_STRING)?;
+                                cdhashes_by_algorithm.push((algorithm, digest.contents.to_vec()));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+            }
+        }
+
+        // walk from the signer's certificate up through the issuers
+        let mut chain = Vec::new();
+        let mut current = signer.and_then(|(issuer, serial)| {
+            certificates.iter().position(|certificate| {
+                certificate.issuer == issuer && certificate.serial == serial
+            })
+        });
+        while let Some(index) = current {
+            let certificate = certificates.remove(index);
+            current = if certificate.issuer == certificate.subject {
+                None
+            } else {
+                certificates
+                    .iter()
+                    .position(|candidate| candidate.subject == certificate.issuer)
+            };
+            chain.push(certificate);
+        }
+        chain.append(&mut certificates);
+
+        Ok(Self {
+            certificates: chain,
+            signing_time,
+            cdhashes,
+            cdhashes_by_algorithm,
+        })
+    }
+
+    pub fn team_id(&self) -> Option<&str> {
+        self.certificates.first().and_then(Certificate::team_id)
+    }
+}
+
+impl CodeSignature {
+    // ad-hoc signatures carry an empty wrapper, which is reported as no signature
+    pub fn cms(&self) -> Result<Option<CmsSignature>, Error> {
+        let data = self.blobs.iter().find_map(|blob| match &blob.blob {
+            Blob::SignatureWrapper(data) if !data.is_empty() => Some(data),
+            _ => None,
+        });
+        data.map(|data| CmsSignature::parse(data)).transpose()
+    }
+}
+
+impl MachO {
+    pub fn cms_signature<R: Read + Seek>(&self, r: &mut R) -> Result<Option<CmsSignature>, Error> {
+        match self.code_signature(r)? {
+            Some(signature) => signature.cms(),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // produced by `openssl cms -sign -binary -stream -outform DER`, which like codesign emits
+    // indefinite lengths for the constructed objects
+    #[test]
+    fn indefinite_length_signature() {
+        let signature =
+            CmsSignature::parse(include_bytes!("../tests/fixtures/openssl_cms_ber.der")).unwrap();
+        assert_eq!(signature.certificates.len(), 1);
+        assert_eq!(
+            signature.certificates[0].subject.common_name(),
+            Some("Developer ID Application: Example (ABCDE12345)")
+        );
+        assert_eq!(signature.team_id(), Some("ABCDE12345"));
+        assert_eq!(
+            signature.signing_time.as_deref(),
+            Some("2026-10-17T02:56:23Z")
+        );
+    }
+}
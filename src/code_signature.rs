@@ -0,0 +1,763 @@
+use crate::command::LoadCommand;
+use crate::constants;
+use crate::error::Error;
+use crate::extractor::{c_string, read_at, Extractor, Insertable, Inserter};
+use crate::macho::MachO;
+use crate::{extract, extractable};
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha384};
+use std::convert::TryInto;
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+extractable!(BlobIndex {
+    slot_type: u32,
+    offset: u32,
+});
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SlotType {
+    CodeDirectory,
+    InfoPlist,
+    Requirements,
+    ResourceDirectory,
+    Application,
+    Entitlements,
+    RepSpecific,
+    DerEntitlements,
+    AlternateCodeDirectory(u32),
+    Signature,
+    Unknown(u32),
+}
+
+impl SlotType {
+    pub fn from_u32(v: u32) -> Self {
+        match v {
+            constants::CSSLOT_CODEDIRECTORY => Self::CodeDirectory,
+            constants::CSSLOT_INFOSLOT => Self::InfoPlist,
+            constants::CSSLOT_REQUIREMENTS => Self::Requirements,
+            constants::CSSLOT_RESOURCEDIR => Self::ResourceDirectory,
+            constants::CSSLOT_APPLICATION => Self::Application,
+            constants::CSSLOT_ENTITLEMENTS => Self::Entitlements,
+            constants::CSSLOT_REP_SPECIFIC => Self::RepSpecific,
+            constants::CSSLOT_DER_ENTITLEMENTS => Self::DerEntitlements,
+            constants::CSSLOT_ALTERNATE_CODEDIRECTORIES
+                ..=constants::CSSLOT_ALTERNATE_CODEDIRECTORY_LIMIT => {
+                Self::AlternateCodeDirectory(v - constants::CSSLOT_ALTERNATE_CODEDIRECTORIES)
+            }
+            constants::CSSLOT_SIGNATURESLOT => Self::Signature,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+
+    pub fn to_u32(self) -> u32 {
+        match self {
+            Self::CodeDirectory => constants::CSSLOT_CODEDIRECTORY,
+            Self::InfoPlist => constants::CSSLOT_INFOSLOT,
+            Self::Requirements => constants::CSSLOT_REQUIREMENTS,
+            Self::ResourceDirectory => constants::CSSLOT_RESOURCEDIR,
+            Self::Application => constants::CSSLOT_APPLICATION,
+            Self::Entitlements => constants::CSSLOT_ENTITLEMENTS,
+            Self::RepSpecific => constants::CSSLOT_REP_SPECIFIC,
+            Self::DerEntitlements => constants::CSSLOT_DER_ENTITLEMENTS,
+            Self::AlternateCodeDirectory(index) => {
+                constants::CSSLOT_ALTERNATE_CODEDIRECTORIES + index
+            }
+            Self::Signature => constants::CSSLOT_SIGNATURESLOT,
+            Self::Unknown(value) => value,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum HashType {
+    Sha1,
+    Sha256,
+    Sha256Truncated,
+    Sha384,
+    Unknown(u8),
+}
+
+impl HashType {
+    pub fn from_u8(v: u8) -> Self {
+        match v {
+            constants::CS_HASHTYPE_SHA1 => Self::Sha1,
+            constants::CS_HASHTYPE_SHA256 => Self::Sha256,
+            constants::CS_HASHTYPE_SHA256_TRUNCATED => Self::Sha256Truncated,
+            constants::CS_HASHTYPE_SHA384 => Self::Sha384,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::Sha1 => constants::CS_HASHTYPE_SHA1,
+            Self::Sha256 => constants::CS_HASHTYPE_SHA256,
+            Self::Sha256Truncated => constants::CS_HASHTYPE_SHA256_TRUNCATED,
+            Self::Sha384 => constants::CS_HASHTYPE_SHA384,
+            Self::Unknown(value) => value,
+        }
+    }
+}
+
+// fields that are newer than the directory's version are left zeroed
+#[derive(PartialEq, Debug)]
+pub struct CodeDirectory {
+    pub version: u32,
+    pub flags: u32,
+    pub hash_offset: u32,
+    pub ident_offset: u32,
+    pub n_special_slots: u32,
+    pub n_code_slots: u32,
+    pub code_limit: u32,
+    pub hash_size: u8,
+    pub hash_type: HashType,
+    pub platform: u8,
+    pub page_size: u8,
+    pub scatter_offset: u32,
+    pub team_offset: u32,
+    pub code_limit_64: u64,
+    pub exec_seg_base: u64,
+    pub exec_seg_limit: u64,
+    pub exec_seg_flags: u64,
+    pub runtime: u32,
+    pub pre_encrypt_offset: u32,
+    pub identifier: String,
+    pub team_id: Option<String>,
+    // special_slots[0] is slot -1 (the Info.plist hash), and so on
+    pub special_slots: Vec<Vec<u8>>,
+    pub code_slots: Vec<Vec<u8>>,
+}
+
+impl CodeDirectory {
+    fn parse(blob: &[u8], base: u64) -> Result<Self, Error> {
+        let mut cursor = Cursor::new(blob);
+        let mut e = Extractor::big_endian(&mut cursor);
+        e.reader.seek(SeekFrom::Start(8))?;
+        let version: u32 = extract!(e);
+        let flags: u32 = extract!(e);
+        let hash_offset: u32 = extract!(e);
+        let ident_offset: u32 = extract!(e);
+        let n_special_slots: u32 = extract!(e);
+        let n_code_slots: u32 = extract!(e);
+        let code_limit: u32 = extract!(e);
+        let hash_size: u8 = extract!(e);
+        let hash_type: u8 = extract!(e);
+        let platform: u8 = extract!(e);
+        let page_size: u8 = extract!(e);
+        let _spare2: u32 = extract!(e);
+
+        let mut scatter_offset = 0;
+        let mut team_offset = 0;
+        let mut code_limit_64 = 0;
+        let mut exec_seg_base = 0;
+        let mut exec_seg_limit = 0;
+        let mut exec_seg_flags = 0;
+        let mut runtime = 0;
+        let mut pre_encrypt_offset = 0;
+        if version >= constants::CS_SUPPORTSSCATTER {
+            scatter_offset = extract!(e);
+        }
+        if version >= constants::CS_SUPPORTSTEAMID {
+            team_offset = extract!(e);
+        }
+        if version >= constants::CS_SUPPORTSCODELIMIT64 {
+            let _spare3: u32 = extract!(e);
+            code_limit_64 = extract!(e);
+        }
+        if version >= constants::CS_SUPPORTSEXECSEG {
+            exec_seg_base = extract!(e);
+            exec_seg_limit = extract!(e);
+            exec_seg_flags = extract!(e);
+        }
+        if version >= constants::CS_SUPPORTSRUNTIME {
+            runtime = extract!(e);
+            pre_encrypt_offset = extract!(e);
+        }
+
+        let string_at = |offset: u32| -> Result<String, Error> {
+            c_string(
+                blob.get(offset as usize..)
+                    .ok_or(Error::UnexpectedEndOfData(base + offset as u64))?,
+            )
+        };
+        let identifier = string_at(ident_offset)?;
+        let team_id = match team_offset {
+            0 => None,
+            offset => Some(string_at(offset)?),
+        };
+
+        // check that every slot lies within the blob before reading any of them
+        if hash_size == 0 {
+            return Err(Error::InvalidHashSize(hash_size));
+        }
+        let special_size = n_special_slots as u64 * hash_size as u64;
+        let code_size = n_code_slots as u64 * hash_size as u64;
+        if special_size > hash_offset as u64 || hash_offset as u64 + code_size > blob.len() as u64 {
+            return Err(Error::UnexpectedEndOfData(base + hash_offset as u64));
+        }
+
+        // special slots are indexed backwards from the hash offset
+        let hash_at = |offset: u64| -> Result<Vec<u8>, Error> {
+            Ok(blob
+                .get(offset as usize..offset as usize + hash_size as usize)
+                .ok_or(Error::UnexpectedEndOfData(base + offset))?
+                .to_vec())
+        };
+        let mut special_slots = Vec::new();
+        for slot in 1..=n_special_slots as u64 {
+            let offset = (hash_offset as u64)
+                .checked_sub(slot * hash_size as u64)
+                .ok_or(Error::UnexpectedEndOfData(base))?;
+            special_slots.push(hash_at(offset)?);
+        }
+        let mut code_slots = Vec::new();
+        for slot in 0..n_code_slots as u64 {
+            code_slots.push(hash_at(hash_offset as u64 + slot * hash_size as u64)?);
+        }
+
+        Ok(Self {
+            version,
+            flags,
+            hash_offset,
+            ident_offset,
+            n_special_slots,
+            n_code_slots,
+            code_limit,
+            hash_size,
+            hash_type: HashType::from_u8(hash_type),
+            platform,
+            page_size,
+            scatter_offset,
+            team_offset,
+            code_limit_64,
+            exec_seg_base,
+            exec_seg_limit,
+            exec_seg_flags,
+            runtime,
+            pre_encrypt_offset,
+            identifier,
+            team_id,
+            special_slots,
+            code_slots,
+        })
+    }
+
+    // strings and hashes are placed at the offsets recorded in the directory
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut cursor = Cursor::new(Vec::new());
+        let mut i = Inserter::big_endian(&mut cursor);
+        constants::CSMAGIC_CODEDIRECTORY.insert_into(&mut i)?;
+        0u32.insert_into(&mut i)?;
+        self.version.insert_into(&mut i)?;
+        self.flags.insert_into(&mut i)?;
+        self.hash_offset.insert_into(&mut i)?;
+        self.ident_offset.insert_into(&mut i)?;
+        self.n_special_slots.insert_into(&mut i)?;
+        self.n_code_slots.insert_into(&mut i)?;
+        self.code_limit.insert_into(&mut i)?;
+        self.hash_size.insert_into(&mut i)?;
+        self.hash_type.to_u8().insert_into(&mut i)?;
+        self.platform.insert_into(&mut i)?;
+        self.page_size.insert_into(&mut i)?;
+        0u32.insert_into(&mut i)?;
+        if self.version >= constants::CS_SUPPORTSSCATTER {
+            self.scatter_offset.insert_into(&mut i)?;
+        }
+        if self.version >= constants::CS_SUPPORTSTEAMID {
+            self.team_offset.insert_into(&mut i)?;
+        }
+        if self.version >= constants::CS_SUPPORTSCODELIMIT64 {
+            0u32.insert_into(&mut i)?;
+            self.code_limit_64.insert_into(&mut i)?;
+        }
+        if self.version >= constants::CS_SUPPORTSEXECSEG {
+            self.exec_seg_base.insert_into(&mut i)?;
+            self.exec_seg_limit.insert_into(&mut i)?;
+            self.exec_seg_flags.insert_into(&mut i)?;
+        }
+        if self.version >= constants::CS_SUPPORTSRUNTIME {
+            self.runtime.insert_into(&mut i)?;
+            self.pre_encrypt_offset.insert_into(&mut i)?;
+        }
+
+        let mut blob = cursor.into_inner();
+        let mut place = |offset: u64, data: &[u8]| {
+            let end = offset as usize + data.len();
+            if blob.len() < end {
+                blob.resize(end, 0);
+            }
+            blob[offset as usize..end].copy_from_slice(data);
+        };
+        place(self.ident_offset as u64, self.identifier.as_bytes());
+        place(
+            self.ident_offset as u64 + self.identifier.len() as u64,
+            &[0],
+        );
+        if let Some(team_id) = &self.team_id {
+            place(self.team_offset as u64, team_id.as_bytes());
+            place(self.team_offset as u64 + team_id.len() as u64, &[0]);
+        }
+        for (index, hash) in self.special_slots.iter().enumerate() {
+            let offset = (self.hash_offset as u64)
+                .checked_sub((index as u64 + 1) * self.hash_size as u64)
+                .ok_or(Error::InvalidBlobLength(self.hash_offset as u64))?;
+            place(offset, hash);
+        }
+        for (index, hash) in self.code_slots.iter().enumerate() {
+            place(
+                self.hash_offset as u64 + index as u64 * self.hash_size as u64,
+                hash,
+            );
+        }
+        let length = blob.len() as u32;
+        blob[4..8].copy_from_slice(&length.to_be_bytes());
+        Ok(blob)
+    }
+}
+
+// wraps a payload in a blob header
+pub fn blob(magic: u32, payload: &[u8]) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(payload.len() + 8);
+    blob.extend_from_slice(&magic.to_be_bytes());
+    blob.extend_from_slice(&(payload.len() as u32 + 8).to_be_bytes());
+    blob.extend_from_slice(payload);
+    blob
+}
+
+// builds a SuperBlob from (slot, blob) pairs, in the order given
+pub fn super_blob(blobs: &[(SlotType, Vec<u8>)]) -> Vec<u8> {
+    let mut payload = Vec::new();
+    payload.extend_from_slice(&(blobs.len() as u32).to_be_bytes());
+    let mut offset = 12 + 8 * blobs.len() as u32;
+    for (slot, data) in blobs {
+        payload.extend_from_slice(&slot.to_u32().to_be_bytes());
+        payload.extend_from_slice(&offset.to_be_bytes());
+        offset += data.len() as u32;
+    }
+    for (_, data) in blobs {
+        payload.extend_from_slice(data);
+    }
+    blob(constants::CSMAGIC_EMBEDDED_SIGNATURE, &payload)
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Requirement {
+    pub kind: u32,
+    pub expression: Vec<u8>,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Blob {
+    CodeDirectory(CodeDirectory),
+    Requirements(Vec<(u32, Requirement)>),
+    Requirement(Requirement),
+    Entitlements(Vec<u8>),
+    DerEntitlements(Vec<u8>),
+    SignatureWrapper(Vec<u8>),
+    Unknown { magic: u32, data: Vec<u8> },
+}
+
+// splits a blob into its magic and the bytes covered by its length
+fn blob_at(data: &[u8], offset: u32, base: u64) -> Result<(u32, &[u8]), Error> {
+    let header = data
+        .get(offset as usize..offset as usize + 8)
+        .ok_or(Error::UnexpectedEndOfData(base + offset as u64))?;
+    let magic = u32::from_be_bytes(header[0..4].try_into().unwrap());
+    let length = u32::from_be_bytes(header[4..8].try_into().unwrap());
+    if length < 8 {
+        return Err(Error::InvalidBlobLength(base + offset as u64));
+    }
+    let blob = data
+        .get(offset as usize..offset as usize + length as usize)
+        .ok_or(Error::UnexpectedEndOfData(base + offset as u64))?;
+    Ok((magic, blob))
+}
+
+fn requirement(blob: &[u8], base: u64) -> Result<Requirement, Error> {
+    let kind = blob
+        .get(8..12)
+        .ok_or(Error::UnexpectedEndOfData(base + 8))?;
+    Ok(Requirement {
+        kind: u32::from_be_bytes(kind.try_into().unwrap()),
+        expression: blob[12..].to_vec(),
+    })
+}
+
+impl Blob {
+    pub fn parse(blob: &[u8], base: u64) -> Result<Self, Error> {
+        let (magic, blob) = blob_at(blob, 0, base)?;
+        Ok(match magic {
+            constants::CSMAGIC_CODEDIRECTORY => {
+                Self::CodeDirectory(CodeDirectory::parse(blob, base)?)
+            }
+            constants::CSMAGIC_REQUIREMENTS => {
+                let mut cursor = Cursor::new(blob);
+                let mut e = Extractor::big_endian(&mut cursor);
+                e.reader.seek(SeekFrom::Start(8))?;
+                let count: u32 = extract!(e);
+                let mut index = Vec::new();
+                for _ in 0..count {
+                    let entry: BlobIndex = extract!(e);
+                    index.push(entry);
+                }
+                let mut requirements = Vec::new();
+                for entry in index {
+                    let (_, data) = blob_at(blob, entry.offset, base)?;
+                    requirements.push((
+                        entry.slot_type,
+                        requirement(data, base + entry.offset as u64)?,
+                    ));
+                }
+                Self::Requirements(requirements)
+            }
+            constants::CSMAGIC_REQUIREMENT => Self::Requirement(requirement(blob, base)?),
+            constants::CSMAGIC_EMBEDDED_ENTITLEMENTS => Self::Entitlements(blob[8..].to_vec()),
+            constants::CSMAGIC_EMBEDDED_DER_ENTITLEMENTS => {
+                Self::DerEntitlements(blob[8..].to_vec())
+            }
+            constants::CSMAGIC_BLOBWRAPPER => Self::SignatureWrapper(blob[8..].to_vec()),
+            magic => Self::Unknown {
+                magic,
+                data: blob[8..].to_vec(),
+            },
+        })
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct SignatureBlob {
+    pub slot: SlotType,
+    pub offset: u32,
+    // the complete blob, including its magic and length
+    pub data: Vec<u8>,
+    pub blob: Blob,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct CodeSignature {
+    pub blobs: Vec<SignatureBlob>,
+}
+
+impl CodeSignature {
+    pub fn parse(data: &[u8], base: u64) -> Result<Self, Error> {
+        let (magic, data) = blob_at(data, 0, base)?;
+        if magic != constants::CSMAGIC_EMBEDDED_SIGNATURE {
+            return Err(Error::InvalidBlobMagic(magic));
+        }
+        let mut cursor = Cursor::new(data);
+        let mut e = Extractor::big_endian(&mut cursor);
+        e.reader.seek(SeekFrom::Start(8))?;
+        let count: u32 = extract!(e);
+        let mut index = Vec::new();
+        for _ in 0..count {
+            let entry: BlobIndex = extract!(e);
+            index.push(entry);
+        }
+
+        let mut blobs = Vec::new();
+        for entry in index {
+            let (_, blob) = blob_at(data, entry.offset, base)?;
+            blobs.push(SignatureBlob {
+                slot: SlotType::from_u32(entry.slot_type),
+                offset: entry.offset,
+                data: blob.to_vec(),
+                blob: Blob::parse(blob, base + entry.offset as u64)?,
+            });
+        }
+        Ok(Self { blobs })
+    }
+
+    pub fn blob(&self, slot: SlotType) -> Option<&SignatureBlob> {
+        self.blobs.iter().find(|blob| blob.slot == slot)
+    }
+
+    pub fn code_directories(&self) -> Vec<&CodeDirectory> {
+        self.blobs
+            .iter()
+            .filter_map(|blob| match &blob.blob {
+                Blob::CodeDirectory(directory) => Some(directory),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+impl MachO {
+    pub fn code_signature<R: Read + Seek>(
+        &self,
+        r: &mut R,
+    ) -> Result<Option<CodeSignature>, Error> {
+        let command = self.commands.iter().find_map(|command| match command {
+            LoadCommand::CodeSignature(command) => Some(command),
+            _ => None,
+        });
+        match command {
+            Some(command) => {
+                let data = read_at(r, command.dataoff as u64, command.datasize as u64)?;
+                Ok(Some(CodeSignature::parse(&data, command.dataoff as u64)?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+impl HashType {
+    pub fn digest(self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        Ok(match self {
+            Self::Sha1 => Sha1::digest(data).to_vec(),
+            Self::Sha256 => Sha256::digest(data).to_vec(),
+            Self::Sha256Truncated => Sha256::digest(data)[..20].to_vec(),
+            Self::Sha384 => Sha384::digest(data).to_vec(),
+            Self::Unknown(hash_type) => return Err(Error::UnsupportedHashType(hash_type)),
+        })
+    }
+}
+
+impl CodeDirectory {
+    pub fn code_limit(&self) -> u64 {
+        if self.code_limit_64 != 0 {
+            self.code_limit_64
+        } else {
+            self.code_limit as u64
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct CodeDirectoryVerification {
+    pub slot: SlotType,
+    pub hash_type: HashType,
+    pub cdhash: Vec<u8>,
+    pub mismatched_pages: Vec<usize>,
+    pub mismatched_special_slots: Vec<SlotType>,
+}
+
+impl CodeDirectoryVerification {
+    pub fn is_valid(&self) -> bool {
+        self.mismatched_pages.is_empty() && self.mismatched_special_slots.is_empty()
+    }
+}
+
+impl CodeSignature {
+    // the Info.plist, resources and application slots hash files outside the binary, so only the
+    // Info.plist is checked, and only when provided
+    pub fn verify<R: Read + Seek>(
+        &self,
+        r: &mut R,
+        info_plist: Option<&[u8]>,
+    ) -> Result<Vec<CodeDirectoryVerification>, Error> {
+        let mut verifications = Vec::new();
+        for blob in &self.blobs {
+            let directory = match &blob.blob {
+                Blob::CodeDirectory(directory) => directory,
+                _ => continue,
+            };
+            let hash = |data: &[u8]| -> Result<Vec<u8>, Error> {
+                let mut digest = directory.hash_type.digest(data)?;
+                digest.truncate(directory.hash_size as usize);
+                Ok(digest)
+            };
+
+            // a page size of zero means the whole file is hashed as one page
+            let code_limit = directory.code_limit();
+            let page_size = match directory.page_size {
+                0 => code_limit,
+                shift @ 1..=32 => 1u64 << shift,
+                shift => return Err(Error::InvalidPageSize(shift)),
+            };
+            let mut mismatched_pages = Vec::new();
+            for (index, expected) in directory.code_slots.iter().enumerate() {
+                let start = index as u64 * page_size;
+                let end = std::cmp::min(start.saturating_add(page_size), code_limit);
+                let page = read_at(r, start, end.saturating_sub(start))?;
+                if hash(&page)? != *expected {
+                    mismatched_pages.push(index);
+                }
+            }
+
+            let mut mismatched_special_slots = Vec::new();
+            for (index, expected) in directory.special_slots.iter().enumerate() {
+                let slot = SlotType::from_u32(index as u32 + 1);
+                let data = match slot {
+                    SlotType::InfoPlist => info_plist,
+                    slot => self.blob(slot).map(|blob| blob.data.as_slice()),
+                };
+                let matches = match data {
+                    Some(data) => hash(data)? == *expected,
+                    // a missing component is only an error if it was hashed
+                    None => {
+                        matches!(
+                            slot,
+                            SlotType::InfoPlist
+                                | SlotType::ResourceDirectory
+                                | SlotType::Application
+                        ) || expected.iter().all(|x| *x == 0)
+                    }
+                };
+                if !matches {
+                    mismatched_special_slots.push(slot);
+                }
+            }
+
+            let mut cdhash = directory.hash_type.digest(&blob.data)?;
+            cdhash.truncate(constants::CS_CDHASH_LEN);
+            verifications.push(CodeDirectoryVerification {
+                slot: blob.slot,
+                hash_type: directory.hash_type,
+                cdhash,
+                mismatched_pages,
+                mismatched_special_slots,
+            });
+        }
+        Ok(verifications)
+    }
+}
+
+impl MachO {
+    pub fn verify_code_signature<R: Read + Seek>(
+        &self,
+        r: &mut R,
+        info_plist: Option<&[u8]>,
+    ) -> Result<Option<Vec<CodeDirectoryVerification>>, Error> {
+        match self.code_signature(r)? {
+            Some(signature) => Ok(Some(signature.verify(r, info_plist)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // a version 0x20001 directory for "a" with the given slots
+    fn directory(hash_size: u8, n_code_slots: u32, hashes: &[u8]) -> Vec<u8> {
+        let mut blob = Vec::new();
+        for word in &[
+            constants::CSMAGIC_CODEDIRECTORY,
+            0,
+            0x20001,
+            0,
+            46,
+            44,
+            0,
+            n_code_slots,
+            0x1000,
+        ] {
+            blob.extend_from_slice(&word.to_be_bytes());
+        }
+        blob.extend_from_slice(&[hash_size, constants::CS_HASHTYPE_SHA256, 0, 12]);
+        blob.extend_from_slice(&[0; 4]);
+        blob.extend_from_slice(b"a\0");
+        blob.extend_from_slice(hashes);
+        let length = blob.len() as u32;
+        blob[4..8].copy_from_slice(&length.to_be_bytes());
+        blob
+    }
+
+    #[test]
+    fn code_directory_slots() {
+        let blob = directory(32, 2, &[0x11; 64]);
+        let directory = CodeDirectory::parse(&blob, 0).unwrap();
+        assert_eq!(directory.identifier, "a");
+        assert_eq!(directory.code_slots, [vec![0x11; 32], vec![0x11; 32]]);
+        assert_eq!(directory.to_bytes().unwrap(), blob);
+    }
+
+    #[test]
+    fn invalid_code_directory_slots() {
+        assert!(matches!(
+            CodeDirectory::parse(&directory(0, u32::MAX, &[]), 0),
+            Err(Error::InvalidHashSize(0))
+        ));
+        assert!(matches!(
+            CodeDirectory::parse(&directory(32, u32::MAX, &[0x11; 64]), 0),
+            Err(Error::UnexpectedEndOfData(46))
+        ));
+    }
+
+    // signs `code` the way codesign signs a bundle executable, hashing an Info.plist, resources
+    // and an application alongside the embedded requirements and entitlements
+    fn bundle_signature(code: &[u8], info_plist: &[u8], page_size: u8) -> CodeSignature {
+        let hash = |data: &[u8]| HashType::Sha256.digest(data).unwrap();
+        let requirements = blob(constants::CSMAGIC_REQUIREMENTS, &0u32.to_be_bytes());
+        let entitlements = blob(constants::CSMAGIC_EMBEDDED_ENTITLEMENTS, b"<plist/>");
+        let identifier = "com.example.app".to_string();
+        let ident_offset = 44;
+        let hash_offset = ident_offset + identifier.len() as u32 + 1 + 5 * 32;
+        let code_slots: Vec<_> = code.chunks(1 << 12).map(hash).collect();
+        let directory = CodeDirectory {
+            version: 0x20001,
+            flags: 0,
+            hash_offset,
+            ident_offset,
+            n_special_slots: 5,
+            n_code_slots: code_slots.len() as u32,
+            code_limit: code.len() as u32,
+            hash_size: 32,
+            hash_type: HashType::Sha256,
+            platform: 0,
+            page_size,
+            scatter_offset: 0,
+            team_offset: 0,
+            code_limit_64: 0,
+            exec_seg_base: 0,
+            exec_seg_limit: 0,
+            exec_seg_flags: 0,
+            runtime: 0,
+            pre_encrypt_offset: 0,
+            identifier,
+            team_id: None,
+            special_slots: vec![
+                hash(info_plist),
+                hash(&requirements),
+                hash(b"_CodeSignature/CodeResources"),
+                hash(b"application"),
+                hash(&entitlements),
+            ],
+            code_slots,
+        };
+        let signature = super_blob(&[
+            (SlotType::CodeDirectory, directory.to_bytes().unwrap()),
+            (SlotType::Requirements, requirements),
+            (SlotType::Entitlements, entitlements),
+        ]);
+        CodeSignature::parse(&signature, 0).unwrap()
+    }
+
+    #[test]
+    fn verify_bundle_signature() {
+        let code: Vec<u8> = (0..0x1800u32).map(|x| x as u8).collect();
+        let info_plist = b"<plist><dict/></plist>";
+        let signature = bundle_signature(&code, info_plist, 12);
+
+        let verification = signature
+            .verify(&mut Cursor::new(&code), Some(info_plist))
+            .unwrap();
+        assert!(verification[0].is_valid());
+        let verification = signature.verify(&mut Cursor::new(&code), None).unwrap();
+        assert!(verification[0].is_valid());
+
+        let verification = signature
+            .verify(&mut Cursor::new(&code), Some(b"<plist/>"))
+            .unwrap();
+        assert_eq!(
+            verification[0].mismatched_special_slots,
+            [SlotType::InfoPlist]
+        );
+        let mut modified = code.clone();
+        modified[0x1400] ^= 1;
+        let verification = signature.verify(&mut Cursor::new(&modified), None).unwrap();
+        assert_eq!(verification[0].mismatched_pages, [1]);
+    }
+
+    #[test]
+    fn invalid_page_size() {
+        let code = vec![0u8; 0x1000];
+        let signature = bundle_signature(&code, b"", 64);
+        assert!(matches!(
+            signature.verify(&mut Cursor::new(&code), None),
+            Err(Error::InvalidPageSize(64))
+        ));
+    }
+}
@@ -1,10 +1,12 @@
 use crate::constants;
 use crate::error::Error;
-use crate::extractor::Extractor;
+use crate::extractor::{c_string, Extractor, Insertable, Inserter};
 use crate::header::Header;
-use crate::{extract, extractable};
+use crate::thread::ThreadCommand;
+use crate::version::{Platform, SourceVersion, Tool, Version};
+use crate::{extract, extractable, insert};
 use std::convert::{TryFrom, TryInto};
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
 #[derive(PartialEq)]
 pub struct NameString {
@@ -12,11 +14,11 @@ pub struct NameString {
 }
 
 impl NameString {
-    fn to_string(self) -> String {
+    pub fn into_string(self) -> String {
         self.name
     }
 
-    fn as_str(&self) -> &str {
+    pub fn as_str(&self) -> &str {
         self.name.as_str()
     }
 
@@ -24,15 +26,15 @@ impl NameString {
         let mut data = [0u8; 16];
         r.read_exact(&mut data)?;
         Ok(Self {
-            name: String::from_utf8(data.iter().map(|x| *x).take_while(|x| *x != 0).collect())?,
+            name: String::from_utf8(data.iter().copied().take_while(|x| *x != 0).collect())?,
         })
     }
 
-    fn from_string(name: String) -> Result<Self, Error> {
-        if name.len() > 16 || name.chars().any(|c| !c.is_ascii()) {
+    pub fn from_string(name: String) -> Result<Self, Error> {
+        if name.len() > 16 || !name.is_ascii() {
             Err(Error::BadString(name))
         } else {
-            Ok(Self { name: name })
+            Ok(Self { name })
         }
     }
 }
@@ -51,7 +53,19 @@ impl<'a> TryFrom<&mut Extractor<'a>> for NameString {
     }
 }
 
-extractable!(UuidCommand { uuid: u128 });
+impl Insertable for NameString {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        let mut data = [0u8; 16];
+        data[..self.name.len()].copy_from_slice(self.name.as_bytes());
+        i.writer.write_all(&data)?;
+        Ok(())
+    }
+}
+
+extractable!(UuidCommand {
+    uuid: u128,
+    padding: Vec<u8> = Vec::new(),
+});
 
 extractable!(SegmentCommand {
     segname: NameString,
@@ -64,6 +78,7 @@ extractable!(SegmentCommand {
     nsects: u32,
     flags: u32,
     sections: Vec<Section> = Vec::new(),
+    padding: Vec<u8> = Vec::new(),
 });
 
 extractable!(Section {
@@ -91,6 +106,7 @@ extractable!(SegmentCommand64 {
     nsects: u32,
     flags: u32,
     sections: Vec<Section64> = Vec::new(),
+    padding: Vec<u8> = Vec::new(),
 });
 
 extractable!(Section64 {
@@ -108,11 +124,105 @@ extractable!(Section64 {
     reserved3: u32,
 });
 
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SegmentInfo<'a> {
+    pub segname: &'a str,
+    pub vmaddr: u64,
+    pub vmsize: u64,
+    pub fileoff: u64,
+    pub filesize: u64,
+    pub maxprot: u32,
+    pub initprot: u32,
+    pub flags: u32,
+}
+
+impl<'a> From<&'a SegmentCommand> for SegmentInfo<'a> {
+    fn from(segment: &'a SegmentCommand) -> Self {
+        Self {
+            segname: segment.segname.as_str(),
+            vmaddr: segment.vmaddr as u64,
+            vmsize: segment.vmsize as u64,
+            fileoff: segment.fileoff as u64,
+            filesize: segment.filesize as u64,
+            maxprot: segment.maxprot,
+            initprot: segment.initprot,
+            flags: segment.flags,
+        }
+    }
+}
+
+impl<'a> From<&'a SegmentCommand64> for SegmentInfo<'a> {
+    fn from(segment: &'a SegmentCommand64) -> Self {
+        Self {
+            segname: segment.segname.as_str(),
+            vmaddr: segment.vmaddr,
+            vmsize: segment.vmsize,
+            fileoff: segment.fileoff,
+            filesize: segment.filesize,
+            maxprot: segment.maxprot,
+            initprot: segment.initprot,
+            flags: segment.flags,
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct SectionInfo<'a> {
+    pub sectname: &'a str,
+    pub segname: &'a str,
+    pub addr: u64,
+    pub size: u64,
+    pub offset: u32,
+    pub align: u32,
+    pub flags: u32,
+    pub reserved1: u32,
+    pub reserved2: u32,
+}
+
+impl SectionInfo<'_> {
+    pub fn section_type(&self) -> u8 {
+        (self.flags & constants::SECTION_TYPE) as u8
+    }
+}
+
+impl<'a> From<&'a Section> for SectionInfo<'a> {
+    fn from(section: &'a Section) -> Self {
+        Self {
+            sectname: section.sectname.as_str(),
+            segname: section.segname.as_str(),
+            addr: section.addr as u64,
+            size: section.size as u64,
+            offset: section.offset,
+            align: section.align,
+            flags: section.flags,
+            reserved1: section.reserved1,
+            reserved2: section.reserved2,
+        }
+    }
+}
+
+impl<'a> From<&'a Section64> for SectionInfo<'a> {
+    fn from(section: &'a Section64) -> Self {
+        Self {
+            sectname: section.sectname.as_str(),
+            segname: section.segname.as_str(),
+            addr: section.addr,
+            size: section.size,
+            offset: section.offset,
+            align: section.align,
+            flags: section.flags,
+            reserved1: section.reserved1,
+            reserved2: section.reserved2,
+        }
+    }
+}
+
 extractable!(SymtabCommand {
     symoff: u32,
     nsyms: u32,
     stroff: u32,
     strsize: u32,
+    padding: Vec<u8> = Vec::new(),
 });
 
 extractable!(DysymtabCommand {
@@ -134,11 +244,13 @@ extractable!(DysymtabCommand {
     nextrel: u32,
     locreloff: u32,
     nlocrel: u32,
+    padding: Vec<u8> = Vec::new(),
 });
 
 extractable!(TwoLevelHintsCommand {
     offset: u32,
     nhints: u32,
+    padding: Vec<u8> = Vec::new(),
 });
 
 extractable!(DyldInfoCommand {
@@ -152,13 +264,211 @@ extractable!(DyldInfoCommand {
     lazy_bind_size: u32,
     export_off: u32,
     export_size: u32,
+    padding: Vec<u8> = Vec::new(),
 });
 
 extractable!(LinkeditDataCommand {
     dataoff: u32,
     datasize: u32,
+    padding: Vec<u8> = Vec::new(),
 });
 
+// the name is an lc_str, stored at name_offset from the start of the command
+extractable!(DylibCommand {
+    name_offset: u32,
+    timestamp: u32,
+    current_version: Version,
+    compatibility_version: Version,
+    name: String = String::new(),
+    padding: Vec<u8> = Vec::new(),
+});
+
+impl DylibCommand {
+    // the original padding only fits the original string
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.padding.clear();
+    }
+
+    fn parse(e: &mut Extractor, size: u32) -> Result<Self, Error> {
+        let mut command: DylibCommand = e.try_into()?;
+        command.name = read_lc_str(e, 24, size, command.name_offset)?;
+        Ok(command)
+    }
+
+    fn insert(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.insert_into(i)?;
+        insert_lc_str(i, 24, self.name_offset, &self.name)
+    }
+}
+
+extractable!(RpathCommand {
+    path_offset: u32,
+    path: String = String::new(),
+    padding: Vec<u8> = Vec::new(),
+});
+
+impl RpathCommand {
+    pub fn set_path(&mut self, path: String) {
+        self.path = path;
+        self.padding.clear();
+    }
+
+    fn parse(e: &mut Extractor, size: u32) -> Result<Self, Error> {
+        let mut command: RpathCommand = e.try_into()?;
+        command.path = read_lc_str(e, 12, size, command.path_offset)?;
+        Ok(command)
+    }
+
+    fn insert(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.insert_into(i)?;
+        insert_lc_str(i, 12, self.path_offset, &self.path)
+    }
+}
+
+// also used by LC_DYLD_ENVIRONMENT, where the name is a VARIABLE=value string
+extractable!(DylinkerCommand {
+    name_offset: u32,
+    name: String = String::new(),
+    padding: Vec<u8> = Vec::new(),
+});
+
+impl DylinkerCommand {
+    pub fn set_name(&mut self, name: String) {
+        self.name = name;
+        self.padding.clear();
+    }
+
+    fn parse(e: &mut Extractor, size: u32) -> Result<Self, Error> {
+        let mut command: DylinkerCommand = e.try_into()?;
+        command.name = read_lc_str(e, 12, size, command.name_offset)?;
+        Ok(command)
+    }
+
+    fn insert(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.insert_into(i)?;
+        insert_lc_str(i, 12, self.name_offset, &self.name)
+    }
+}
+
+extractable!(EntryPointCommand {
+    entryoff: u64,
+    stacksize: u64,
+    padding: Vec<u8> = Vec::new(),
+});
+
+extractable!(VersionMinCommand {
+    version: Version,
+    sdk: Version,
+    padding: Vec<u8> = Vec::new(),
+});
+
+extractable!(BuildVersionCommand {
+    platform: Platform,
+    minos: Version,
+    sdk: Version,
+    ntools: u32,
+    tools: Vec<BuildToolVersion> = Vec::new(),
+    padding: Vec<u8> = Vec::new(),
+});
+
+extractable!(BuildToolVersion {
+    tool: Tool,
+    version: Version,
+});
+
+extractable!(SourceVersionCommand {
+    version: SourceVersion,
+    padding: Vec<u8> = Vec::new(),
+});
+
+extractable!(EncryptionInfoCommand {
+    cryptoff: u32,
+    cryptsize: u32,
+    cryptid: u32,
+    padding: Vec<u8> = Vec::new(),
+});
+
+extractable!(EncryptionInfoCommand64 {
+    cryptoff: u32,
+    cryptsize: u32,
+    cryptid: u32,
+    pad: u32,
+    padding: Vec<u8> = Vec::new(),
+});
+
+extractable!(LinkerOptionCommand {
+    count: u32,
+    strings: Vec<String> = Vec::new(),
+    padding: Vec<u8> = Vec::new(),
+});
+
+impl LinkerOptionCommand {
+    fn parse(e: &mut Extractor, size: u32) -> Result<Self, Error> {
+        let mut command: LinkerOptionCommand = e.try_into()?;
+        let mut data = vec![0u8; size.saturating_sub(12) as usize];
+        e.reader.read_exact(&mut data)?;
+        let mut remaining = data.as_slice();
+        for _ in 0..command.count {
+            let length = remaining
+                .iter()
+                .position(|x| *x == 0)
+                .ok_or(Error::InvalidLoadCommandSize(size))?;
+            command.strings.push(c_string(&remaining[..length])?);
+            remaining = &remaining[length + 1..];
+        }
+        // leave the rest to be read as padding
+        e.reader
+            .seek(SeekFrom::Current(-(remaining.len() as i64)))?;
+        Ok(command)
+    }
+
+    fn insert(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.insert_into(i)?;
+        for string in &self.strings {
+            i.writer.write_all(string.as_bytes())?;
+            i.writer.write_all(&[0])?;
+        }
+        Ok(())
+    }
+}
+
+// offset and size locate the note's data in the file
+extractable!(NoteCommand {
+    data_owner: NameString,
+    offset: u64,
+    size: u64,
+    padding: Vec<u8> = Vec::new(),
+});
+
+// reads the string at offset, leaving any bytes after its terminator to be read as padding
+fn read_lc_str(e: &mut Extractor, consumed: u32, size: u32, offset: u32) -> Result<String, Error> {
+    if offset < consumed || offset >= size {
+        return Err(Error::InvalidStringOffset(offset));
+    }
+    let mut data = vec![0u8; (size - consumed) as usize];
+    e.reader.read_exact(&mut data)?;
+    let data = &data[(offset - consumed) as usize..];
+    let length = data
+        .iter()
+        .position(|x| *x == 0)
+        .ok_or_else(|| Error::BadStringParse(data.to_vec()))?;
+    e.reader
+        .seek(SeekFrom::Current(-((data.len() - length - 1) as i64)))?;
+    c_string(data)
+}
+
+fn insert_lc_str(i: &mut Inserter, consumed: u32, offset: u32, string: &str) -> Result<(), Error> {
+    if offset < consumed {
+        return Err(Error::InvalidStringOffset(offset));
+    }
+    i.writer
+        .write_all(&vec![0u8; (offset - consumed) as usize])?;
+    i.writer.write_all(string.as_bytes())?;
+    i.writer.write_all(&[0])?;
+    Ok(())
+}
+
 #[derive(PartialEq, Debug)]
 pub enum LoadCommand {
     Uuid(UuidCommand),
@@ -175,23 +485,48 @@ pub enum LoadCommand {
     DataInCode(LinkeditDataCommand),
     DylibCodeSignDrs(LinkeditDataCommand),
     LinkerOptimizationHint(LinkeditDataCommand),
+    DyldExportsTrie(LinkeditDataCommand),
+    DyldChainedFixups(LinkeditDataCommand),
+    LoadDylib(DylibCommand),
+    IdDylib(DylibCommand),
+    LoadWeakDylib(DylibCommand),
+    ReexportDylib(DylibCommand),
+    LoadUpwardDylib(DylibCommand),
+    LazyLoadDylib(DylibCommand),
+    Rpath(RpathCommand),
+    LoadDylinker(DylinkerCommand),
+    IdDylinker(DylinkerCommand),
+    DyldEnvironment(DylinkerCommand),
+    Main(EntryPointCommand),
+    VersionMinMacosx(VersionMinCommand),
+    VersionMinIphoneos(VersionMinCommand),
+    VersionMinTvos(VersionMinCommand),
+    VersionMinWatchos(VersionMinCommand),
+    BuildVersion(BuildVersionCommand),
+    SourceVersion(SourceVersionCommand),
+    EncryptionInfo(EncryptionInfoCommand),
+    EncryptionInfo64(EncryptionInfoCommand64),
+    LinkerOption(LinkerOptionCommand),
+    Note(NoteCommand),
+    Thread(ThreadCommand),
+    UnixThread(ThreadCommand),
     Unknown { cmd: u32, data: Vec<u8> },
 }
 
 impl LoadCommand {
     pub fn from_reader<R: Read + Seek>(header: &Header, r: &mut R) -> Result<Self, Error> {
-        let initial_position = r.seek(SeekFrom::Current(0))?;
+        let initial_position = r.stream_position()?;
         let mut e = header.magic.get_extractor(r);
         let cmd: u32 = extract!(e);
         let size: u32 = extract!(e);
         if (size < 8)
-            || (header.is_32_bit() && size % 4 != 0)
-            || (header.is_64_bit() && size % 8 != 0)
+            || (header.is_32_bit() && !size.is_multiple_of(4))
+            || (header.is_64_bit() && !size.is_multiple_of(8))
         {
             return Err(Error::InvalidLoadCommandSize(size));
         }
 
-        let command = match cmd {
+        let mut command = match cmd {
             constants::LC_UUID => LoadCommand::Uuid(extract!(e)),
             constants::LC_SEGMENT => {
                 let mut command: SegmentCommand = extract!(e);
@@ -220,16 +555,363 @@ impl LoadCommand {
             constants::LC_LINKER_OPTIMIZATION_HINT => {
                 LoadCommand::LinkerOptimizationHint(extract!(e))
             }
+            constants::LC_DYLD_EXPORTS_TRIE => LoadCommand::DyldExportsTrie(extract!(e)),
+            constants::LC_DYLD_CHAINED_FIXUPS => LoadCommand::DyldChainedFixups(extract!(e)),
+            constants::LC_LOAD_DYLIB => LoadCommand::LoadDylib(DylibCommand::parse(&mut e, size)?),
+            constants::LC_ID_DYLIB => LoadCommand::IdDylib(DylibCommand::parse(&mut e, size)?),
+            constants::LC_LOAD_WEAK_DYLIBL => {
+                LoadCommand::LoadWeakDylib(DylibCommand::parse(&mut e, size)?)
+            }
+            constants::LC_REEXPORT_DYLIB => {
+                LoadCommand::ReexportDylib(DylibCommand::parse(&mut e, size)?)
+            }
+            constants::LC_LOAD_UPWARD_DYLIB => {
+                LoadCommand::LoadUpwardDylib(DylibCommand::parse(&mut e, size)?)
+            }
+            constants::LC_LAZY_LOAD_DYLIB => {
+                LoadCommand::LazyLoadDylib(DylibCommand::parse(&mut e, size)?)
+            }
+            constants::LC_RPATH => LoadCommand::Rpath(RpathCommand::parse(&mut e, size)?),
+            constants::LC_LOAD_DYLINKER => {
+                LoadCommand::LoadDylinker(DylinkerCommand::parse(&mut e, size)?)
+            }
+            constants::LC_ID_DYLINKER => {
+                LoadCommand::IdDylinker(DylinkerCommand::parse(&mut e, size)?)
+            }
+            constants::LC_DYLD_ENVIRONMENT => {
+                LoadCommand::DyldEnvironment(DylinkerCommand::parse(&mut e, size)?)
+            }
+            constants::LC_MAIN => LoadCommand::Main(extract!(e)),
+            constants::LC_VERSION_MIN_MACOSX => LoadCommand::VersionMinMacosx(extract!(e)),
+            constants::LC_VERSION_MIN_IPHONEOS => LoadCommand::VersionMinIphoneos(extract!(e)),
+            constants::LC_VERSION_MIN_TVOS => LoadCommand::VersionMinTvos(extract!(e)),
+            constants::LC_VERSION_MIN_WATCHOS => LoadCommand::VersionMinWatchos(extract!(e)),
+            constants::LC_BUILD_VERSION => {
+                let mut command: BuildVersionCommand = extract!(e);
+                for _ in 0..command.ntools {
+                    command.tools.push(extract!(e));
+                }
+                LoadCommand::BuildVersion(command)
+            }
+            constants::LC_SOURCE_VERSION => LoadCommand::SourceVersion(extract!(e)),
+            constants::LC_ENCRYPTION_INFO => LoadCommand::EncryptionInfo(extract!(e)),
+            constants::LC_ENCRYPTION_INFO_64 => LoadCommand::EncryptionInfo64(extract!(e)),
+            constants::LC_LINKER_OPTION => {
+                LoadCommand::LinkerOption(LinkerOptionCommand::parse(&mut e, size)?)
+            }
+            constants::LC_NOTE => LoadCommand::Note(extract!(e)),
+            constants::LC_THREAD => {
+                LoadCommand::Thread(ThreadCommand::parse(&mut e, size, &header.cputype)?)
+            }
+            constants::LC_UNIXTHREAD => {
+                LoadCommand::UnixThread(ThreadCommand::parse(&mut e, size, &header.cputype)?)
+            }
             _ => {
                 let mut data = vec![0u8; (size - 8) as usize];
                 r.read_exact(&mut data)?;
-                Self::Unknown {
-                    cmd: cmd,
-                    data: data,
-                }
+                Self::Unknown { cmd, data }
             }
         };
-        r.seek(SeekFrom::Start(initial_position + size as u64))?;
+        // keep whatever follows the parsed fields so the command can be written back unchanged
+        let consumed = r.stream_position()? - initial_position;
+        if consumed > size as u64 {
+            return Err(Error::InvalidLoadCommandSize(size));
+        }
+        if let Some(padding) = command.padding_mut() {
+            padding.resize((size as u64 - consumed) as usize, 0);
+            r.read_exact(padding)?;
+        }
         Ok(command)
     }
+
+    fn padding(&self) -> &[u8] {
+        match self {
+            Self::Uuid(command) => &command.padding,
+            Self::Segment(command) => &command.padding,
+            Self::Segment64(command) => &command.padding,
+            Self::Symtab(command) => &command.padding,
+            Self::Dysymtab(command) => &command.padding,
+            Self::TwoLevelHints(command) => &command.padding,
+            Self::DyldInfo(command) | Self::DyldInfoOnly(command) => &command.padding,
+            Self::CodeSignature(command)
+            | Self::SegmentSplitInfo(command)
+            | Self::FunctionStarts(command)
+            | Self::DataInCode(command)
+            | Self::DylibCodeSignDrs(command)
+            | Self::LinkerOptimizationHint(command)
+            | Self::DyldExportsTrie(command)
+            | Self::DyldChainedFixups(command) => &command.padding,
+            Self::LoadDylib(command)
+            | Self::IdDylib(command)
+            | Self::LoadWeakDylib(command)
+            | Self::ReexportDylib(command)
+            | Self::LoadUpwardDylib(command)
+            | Self::LazyLoadDylib(command) => &command.padding,
+            Self::Rpath(command) => &command.padding,
+            Self::LoadDylinker(command)
+            | Self::IdDylinker(command)
+            | Self::DyldEnvironment(command) => &command.padding,
+            Self::Main(command) => &command.padding,
+            Self::VersionMinMacosx(command)
+            | Self::VersionMinIphoneos(command)
+            | Self::VersionMinTvos(command)
+            | Self::VersionMinWatchos(command) => &command.padding,
+            Self::BuildVersion(command) => &command.padding,
+            Self::SourceVersion(command) => &command.padding,
+            Self::EncryptionInfo(command) => &command.padding,
+            Self::EncryptionInfo64(command) => &command.padding,
+            Self::LinkerOption(command) => &command.padding,
+            Self::Note(command) => &command.padding,
+            Self::Thread(command) | Self::UnixThread(command) => &command.padding,
+            Self::Unknown { .. } => &[],
+        }
+    }
+
+    fn padding_mut(&mut self) -> Option<&mut Vec<u8>> {
+        match self {
+            Self::Uuid(command) => Some(&mut command.padding),
+            Self::Segment(command) => Some(&mut command.padding),
+            Self::Segment64(command) => Some(&mut command.padding),
+            Self::Symtab(command) => Some(&mut command.padding),
+            Self::Dysymtab(command) => Some(&mut command.padding),
+            Self::TwoLevelHints(command) => Some(&mut command.padding),
+            Self::DyldInfo(command) | Self::DyldInfoOnly(command) => Some(&mut command.padding),
+            Self::CodeSignature(command)
+            | Self::SegmentSplitInfo(command)
+            | Self::FunctionStarts(command)
+            | Self::DataInCode(command)
+            | Self::DylibCodeSignDrs(command)
+            | Self::LinkerOptimizationHint(command)
+            | Self::DyldExportsTrie(command)
+            | Self::DyldChainedFixups(command) => Some(&mut command.padding),
+            Self::LoadDylib(command)
+            | Self::IdDylib(command)
+            | Self::LoadWeakDylib(command)
+            | Self::ReexportDylib(command)
+            | Self::LoadUpwardDylib(command)
+            | Self::LazyLoadDylib(command) => Some(&mut command.padding),
+            Self::Rpath(command) => Some(&mut command.padding),
+            Self::LoadDylinker(command)
+            | Self::IdDylinker(command)
+            | Self::DyldEnvironment(command) => Some(&mut command.padding),
+            Self::Main(command) => Some(&mut command.padding),
+            Self::VersionMinMacosx(command)
+            | Self::VersionMinIphoneos(command)
+            | Self::VersionMinTvos(command)
+            | Self::VersionMinWatchos(command) => Some(&mut command.padding),
+            Self::BuildVersion(command) => Some(&mut command.padding),
+            Self::SourceVersion(command) => Some(&mut command.padding),
+            Self::EncryptionInfo(command) => Some(&mut command.padding),
+            Self::EncryptionInfo64(command) => Some(&mut command.padding),
+            Self::LinkerOption(command) => Some(&mut command.padding),
+            Self::Note(command) => Some(&mut command.padding),
+            Self::Thread(command) | Self::UnixThread(command) => Some(&mut command.padding),
+            Self::Unknown { .. } => None,
+        }
+    }
+
+    pub fn cmd(&self) -> u32 {
+        match self {
+            Self::Uuid(_) => constants::LC_UUID,
+            Self::Segment(_) => constants::LC_SEGMENT,
+            Self::Segment64(_) => constants::LC_SEGMENT_64,
+            Self::Symtab(_) => constants::LC_SYMTAB,
+            Self::Dysymtab(_) => constants::LC_DYSYMTAB,
+            Self::TwoLevelHints(_) => constants::LC_TWOLEVEL_HINTS,
+            Self::DyldInfo(_) => constants::LC_DYLD_INFO,
+            Self::DyldInfoOnly(_) => constants::LC_DYLD_INFO_ONLY,
+            Self::CodeSignature(_) => constants::LC_CODE_SIGNATURE,
+            Self::SegmentSplitInfo(_) => constants::LC_SEGMENT_SPLIT_INFO,
+            Self::FunctionStarts(_) => constants::LC_FUNCTION_STARTS,
+            Self::DataInCode(_) => constants::LC_DATA_IN_CODE,
+            Self::DylibCodeSignDrs(_) => constants::LC_DYLIB_CODE_SIGN_DRS,
+            Self::LinkerOptimizationHint(_) => constants::LC_LINKER_OPTIMIZATION_HINT,
+            Self::DyldExportsTrie(_) => constants::LC_DYLD_EXPORTS_TRIE,
+            Self::DyldChainedFixups(_) => constants::LC_DYLD_CHAINED_FIXUPS,
+            Self::LoadDylib(_) => constants::LC_LOAD_DYLIB,
+            Self::IdDylib(_) => constants::LC_ID_DYLIB,
+            Self::LoadWeakDylib(_) => constants::LC_LOAD_WEAK_DYLIBL,
+            Self::ReexportDylib(_) => constants::LC_REEXPORT_DYLIB,
+            Self::LoadUpwardDylib(_) => constants::LC_LOAD_UPWARD_DYLIB,
+            Self::LazyLoadDylib(_) => constants::LC_LAZY_LOAD_DYLIB,
+            Self::Rpath(_) => constants::LC_RPATH,
+            Self::LoadDylinker(_) => constants::LC_LOAD_DYLINKER,
+            Self::IdDylinker(_) => constants::LC_ID_DYLINKER,
+            Self::DyldEnvironment(_) => constants::LC_DYLD_ENVIRONMENT,
+            Self::Main(_) => constants::LC_MAIN,
+            Self::VersionMinMacosx(_) => constants::LC_VERSION_MIN_MACOSX,
+            Self::VersionMinIphoneos(_) => constants::LC_VERSION_MIN_IPHONEOS,
+            Self::VersionMinTvos(_) => constants::LC_VERSION_MIN_TVOS,
+            Self::VersionMinWatchos(_) => constants::LC_VERSION_MIN_WATCHOS,
+            Self::BuildVersion(_) => constants::LC_BUILD_VERSION,
+            Self::SourceVersion(_) => constants::LC_SOURCE_VERSION,
+            Self::EncryptionInfo(_) => constants::LC_ENCRYPTION_INFO,
+            Self::EncryptionInfo64(_) => constants::LC_ENCRYPTION_INFO_64,
+            Self::LinkerOption(_) => constants::LC_LINKER_OPTION,
+            Self::Note(_) => constants::LC_NOTE,
+            Self::Thread(_) => constants::LC_THREAD,
+            Self::UnixThread(_) => constants::LC_UNIXTHREAD,
+            Self::Unknown { cmd, .. } => *cmd,
+        }
+    }
+
+    fn insert_body(&self, i: &mut Inserter) -> Result<(), Error> {
+        match self {
+            Self::Uuid(command) => command.insert_into(i)?,
+            Self::Segment(command) => {
+                command.insert_into(i)?;
+                for section in &command.sections {
+                    section.insert_into(i)?;
+                }
+            }
+            Self::Segment64(command) => {
+                command.insert_into(i)?;
+                for section in &command.sections {
+                    section.insert_into(i)?;
+                }
+            }
+            Self::Symtab(command) => command.insert_into(i)?,
+            Self::Dysymtab(command) => command.insert_into(i)?,
+            Self::TwoLevelHints(command) => command.insert_into(i)?,
+            Self::DyldInfo(command) | Self::DyldInfoOnly(command) => command.insert_into(i)?,
+            Self::CodeSignature(command)
+            | Self::SegmentSplitInfo(command)
+            | Self::FunctionStarts(command)
+            | Self::DataInCode(command)
+            | Self::DylibCodeSignDrs(command)
+            | Self::LinkerOptimizationHint(command)
+            | Self::DyldExportsTrie(command)
+            | Self::DyldChainedFixups(command) => command.insert_into(i)?,
+            Self::LoadDylib(command)
+            | Self::IdDylib(command)
+            | Self::LoadWeakDylib(command)
+            | Self::ReexportDylib(command)
+            | Self::LoadUpwardDylib(command)
+            | Self::LazyLoadDylib(command) => command.insert(i)?,
+            Self::Rpath(command) => command.insert(i)?,
+            Self::LoadDylinker(command)
+            | Self::IdDylinker(command)
+            | Self::DyldEnvironment(command) => command.insert(i)?,
+            Self::Main(command) => command.insert_into(i)?,
+            Self::VersionMinMacosx(command)
+            | Self::VersionMinIphoneos(command)
+            | Self::VersionMinTvos(command)
+            | Self::VersionMinWatchos(command) => command.insert_into(i)?,
+            Self::BuildVersion(command) => {
+                command.insert_into(i)?;
+                for tool in &command.tools {
+                    tool.insert_into(i)?;
+                }
+            }
+            Self::SourceVersion(command) => command.insert_into(i)?,
+            Self::EncryptionInfo(command) => command.insert_into(i)?,
+            Self::EncryptionInfo64(command) => command.insert_into(i)?,
+            Self::LinkerOption(command) => command.insert(i)?,
+            Self::Note(command) => command.insert_into(i)?,
+            Self::Thread(command) | Self::UnixThread(command) => command.insert(i)?,
+            Self::Unknown { data, .. } => i.writer.write_all(data)?,
+        }
+        Ok(())
+    }
+
+    fn to_bytes(&self, header: &Header) -> Result<Vec<u8>, Error> {
+        let mut body = Cursor::new(Vec::new());
+        self.insert_body(&mut header.magic.get_inserter(&mut body))?;
+        // commands are padded to the pointer size, keeping any padding read from the file
+        let mut body = body.into_inner();
+        body.extend_from_slice(self.padding());
+        let alignment = if header.is_64_bit() { 8 } else { 4 };
+        body.resize((body.len() + 8).next_multiple_of(alignment) - 8, 0);
+        Ok(body)
+    }
+
+    pub fn size(&self, header: &Header) -> Result<u32, Error> {
+        Ok(self.to_bytes(header)?.len() as u32 + 8)
+    }
+
+    pub fn write_to<W: Write + Seek>(&self, header: &Header, w: &mut W) -> Result<(), Error> {
+        let body = self.to_bytes(header)?;
+        let mut i = header.magic.get_inserter(w);
+        insert!(i, self.cmd());
+        insert!(i, body.len() as u32 + 8);
+        i.writer.write_all(&body)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header() -> Header {
+        let mut data = Vec::new();
+        for word in &[0xfeedfacfu32, 0x01000007, 3, 2, 1, 0, 0, 0] {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        Header::from_reader(&mut Cursor::new(data)).unwrap()
+    }
+
+    fn dylinker(name: &[u8], size: u32, fill: u8) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&constants::LC_LOAD_DYLINKER.to_le_bytes());
+        data.extend_from_slice(&size.to_le_bytes());
+        data.extend_from_slice(&12u32.to_le_bytes());
+        data.extend_from_slice(name);
+        data.resize(size as usize, fill);
+        data
+    }
+
+    #[test]
+    fn lc_str_padding() {
+        let header = header();
+        let data = dylinker(b"/usr/lib/dyld\0", 40, 0xaa);
+        let mut command = LoadCommand::from_reader(&header, &mut Cursor::new(&data)).unwrap();
+        let mut written = Cursor::new(Vec::new());
+        command.write_to(&header, &mut written).unwrap();
+        assert_eq!(written.into_inner(), data);
+
+        if let LoadCommand::LoadDylinker(command) = &mut command {
+            command.set_name("/usr/lib/dyld2".to_string());
+        }
+        let mut written = Cursor::new(Vec::new());
+        command.write_to(&header, &mut written).unwrap();
+        assert_eq!(written.into_inner(), dylinker(b"/usr/lib/dyld2\0", 32, 0));
+    }
+
+    #[test]
+    fn unterminated_lc_str() {
+        let data = dylinker(b"/usr/lib/dyld", 32, b'a');
+        assert!(matches!(
+            LoadCommand::from_reader(&header(), &mut Cursor::new(data)),
+            Err(Error::BadStringParse(_))
+        ));
+    }
+
+    #[test]
+    fn linker_options() {
+        let header = header();
+        let mut data = Vec::new();
+        data.extend_from_slice(&constants::LC_LINKER_OPTION.to_le_bytes());
+        data.extend_from_slice(&40u32.to_le_bytes());
+        data.extend_from_slice(&2u32.to_le_bytes());
+        data.extend_from_slice(b"-framework\0Foundation\0");
+        data.resize(40, 0);
+        let command = LoadCommand::from_reader(&header, &mut Cursor::new(&data)).unwrap();
+        match &command {
+            LoadCommand::LinkerOption(command) => {
+                assert_eq!(command.strings, ["-framework", "Foundation"])
+            }
+            _ => panic!("expected LC_LINKER_OPTION"),
+        }
+        let mut written = Cursor::new(Vec::new());
+        command.write_to(&header, &mut written).unwrap();
+        assert_eq!(written.into_inner(), data);
+
+        // more strings than the command holds
+        data[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            LoadCommand::from_reader(&header, &mut Cursor::new(&data)),
+            Err(Error::InvalidLoadCommandSize(40))
+        ));
+    }
 }
@@ -1,5 +1,4 @@
-#[allow(non_snake_case)]
-#[allow(dead_code)]
+#![allow(dead_code)]
 
 // CPU types
 pub(crate) const CPU_ARCH_ABI64: u32 = 0x01000000;
@@ -22,6 +21,8 @@ pub(crate) const MH_MAGIC: u32 = 0xfeedface;
 pub(crate) const MH_CIGAM: u32 = 0xcefaedfe;
 pub(crate) const MH_MAGIC_64: u32 = 0xfeedfacf;
 pub(crate) const MH_CIGAM_64: u32 = 0xcffaedfe;
+pub(crate) const FAT_MAGIC: u32 = 0xcafebabe;
+pub(crate) const FAT_MAGIC_64: u32 = 0xcafebabf;
 
 // File types
 pub(crate) const MH_OBJECT: u32 = 0x1;
@@ -92,3 +93,264 @@ pub(crate) const LC_NOTE: u32 = 0x31;
 pub(crate) const LC_BUILD_VERSION: u32 = 0x32;
 pub(crate) const LC_DYLD_EXPORTS_TRIE: u32 = 0x33 | LC_REQ_DYLD;
 pub(crate) const LC_DYLD_CHAINED_FIXUPS: u32 = 0x34 | LC_REQ_DYLD;
+
+// Build version platforms and tools
+pub(crate) const PLATFORM_MACOS: u32 = 1;
+pub(crate) const PLATFORM_IOS: u32 = 2;
+pub(crate) const PLATFORM_TVOS: u32 = 3;
+pub(crate) const PLATFORM_WATCHOS: u32 = 4;
+pub(crate) const PLATFORM_BRIDGEOS: u32 = 5;
+pub(crate) const PLATFORM_MACCATALYST: u32 = 6;
+pub(crate) const PLATFORM_IOSSIMULATOR: u32 = 7;
+pub(crate) const PLATFORM_TVOSSIMULATOR: u32 = 8;
+pub(crate) const PLATFORM_WATCHOSSIMULATOR: u32 = 9;
+pub(crate) const PLATFORM_DRIVERKIT: u32 = 10;
+pub(crate) const PLATFORM_VISIONOS: u32 = 11;
+pub(crate) const PLATFORM_VISIONOSSIMULATOR: u32 = 12;
+pub(crate) const TOOL_CLANG: u32 = 1;
+pub(crate) const TOOL_SWIFT: u32 = 2;
+pub(crate) const TOOL_LD: u32 = 3;
+
+// Section types
+pub(crate) const SECTION_TYPE: u32 = 0x000000ff;
+pub(crate) const S_NON_LAZY_SYMBOL_POINTERS: u8 = 0x6;
+pub(crate) const S_LAZY_SYMBOL_POINTERS: u8 = 0x7;
+pub(crate) const S_SYMBOL_STUBS: u8 = 0x8;
+pub(crate) const S_LAZY_DYLIB_SYMBOL_POINTERS: u8 = 0x10;
+
+// Symbol table entries
+pub(crate) const N_STAB: u8 = 0xe0;
+pub(crate) const N_PEXT: u8 = 0x10;
+pub(crate) const N_TYPE: u8 = 0x0e;
+pub(crate) const N_EXT: u8 = 0x01;
+pub(crate) const N_UNDF: u8 = 0x0;
+pub(crate) const N_ABS: u8 = 0x2;
+pub(crate) const N_SECT: u8 = 0xe;
+pub(crate) const N_PBUD: u8 = 0xc;
+pub(crate) const N_INDR: u8 = 0xa;
+pub(crate) const NO_SECT: u8 = 0;
+pub(crate) const REFERENCE_TYPE: u16 = 0x7;
+pub(crate) const REFERENCED_DYNAMICALLY: u16 = 0x10;
+pub(crate) const N_NO_DEAD_STRIP: u16 = 0x20;
+pub(crate) const N_WEAK_REF: u16 = 0x40;
+pub(crate) const N_WEAK_DEF: u16 = 0x80;
+pub(crate) const N_REF_TO_WEAK: u16 = 0x80;
+pub(crate) const N_ARM_THUMB_DEF: u16 = 0x8;
+pub(crate) const N_SYMBOL_RESOLVER: u16 = 0x100;
+pub(crate) const N_ALT_ENTRY: u16 = 0x200;
+pub(crate) const SELF_LIBRARY_ORDINAL: u8 = 0x0;
+pub(crate) const DYNAMIC_LOOKUP_ORDINAL: u8 = 0xfe;
+pub(crate) const EXECUTABLE_ORDINAL: u8 = 0xff;
+pub(crate) const INDIRECT_SYMBOL_LOCAL: u32 = 0x80000000;
+pub(crate) const INDIRECT_SYMBOL_ABS: u32 = 0x40000000;
+
+// Rebase opcodes
+pub(crate) const REBASE_TYPE_POINTER: u8 = 1;
+pub(crate) const REBASE_TYPE_TEXT_ABSOLUTE32: u8 = 2;
+pub(crate) const REBASE_TYPE_TEXT_PCREL32: u8 = 3;
+pub(crate) const REBASE_OPCODE_MASK: u8 = 0xF0;
+pub(crate) const REBASE_IMMEDIATE_MASK: u8 = 0x0F;
+pub(crate) const REBASE_OPCODE_DONE: u8 = 0x00;
+pub(crate) const REBASE_OPCODE_SET_TYPE_IMM: u8 = 0x10;
+pub(crate) const REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB: u8 = 0x20;
+pub(crate) const REBASE_OPCODE_ADD_ADDR_ULEB: u8 = 0x30;
+pub(crate) const REBASE_OPCODE_ADD_ADDR_IMM_SCALED: u8 = 0x40;
+pub(crate) const REBASE_OPCODE_DO_REBASE_IMM_TIMES: u8 = 0x50;
+pub(crate) const REBASE_OPCODE_DO_REBASE_ULEB_TIMES: u8 = 0x60;
+pub(crate) const REBASE_OPCODE_DO_REBASE_ADD_ADDR_ULEB: u8 = 0x70;
+pub(crate) const REBASE_OPCODE_DO_REBASE_ULEB_TIMES_SKIPPING_ULEB: u8 = 0x80;
+
+// Bind opcodes
+pub(crate) const BIND_TYPE_POINTER: u8 = 1;
+pub(crate) const BIND_TYPE_TEXT_ABSOLUTE32: u8 = 2;
+pub(crate) const BIND_TYPE_TEXT_PCREL32: u8 = 3;
+pub(crate) const BIND_SPECIAL_DYLIB_SELF: i8 = 0;
+pub(crate) const BIND_SPECIAL_DYLIB_MAIN_EXECUTABLE: i8 = -1;
+pub(crate) const BIND_SPECIAL_DYLIB_FLAT_LOOKUP: i8 = -2;
+pub(crate) const BIND_SPECIAL_DYLIB_WEAK_LOOKUP: i8 = -3;
+pub(crate) const BIND_SYMBOL_FLAGS_WEAK_IMPORT: u8 = 0x1;
+pub(crate) const BIND_SYMBOL_FLAGS_NON_WEAK_DEFINITION: u8 = 0x8;
+pub(crate) const BIND_OPCODE_MASK: u8 = 0xF0;
+pub(crate) const BIND_IMMEDIATE_MASK: u8 = 0x0F;
+pub(crate) const BIND_OPCODE_DONE: u8 = 0x00;
+pub(crate) const BIND_OPCODE_SET_DYLIB_ORDINAL_IMM: u8 = 0x10;
+pub(crate) const BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB: u8 = 0x20;
+pub(crate) const BIND_OPCODE_SET_DYLIB_SPECIAL_IMM: u8 = 0x30;
+pub(crate) const BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM: u8 = 0x40;
+pub(crate) const BIND_OPCODE_SET_TYPE_IMM: u8 = 0x50;
+pub(crate) const BIND_OPCODE_SET_ADDEND_SLEB: u8 = 0x60;
+pub(crate) const BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB: u8 = 0x70;
+pub(crate) const BIND_OPCODE_ADD_ADDR_ULEB: u8 = 0x80;
+pub(crate) const BIND_OPCODE_DO_BIND: u8 = 0x90;
+pub(crate) const BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB: u8 = 0xA0;
+pub(crate) const BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED: u8 = 0xB0;
+pub(crate) const BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB: u8 = 0xC0;
+pub(crate) const BIND_OPCODE_THREADED: u8 = 0xD0;
+
+// Export trie flags
+pub(crate) const EXPORT_SYMBOL_FLAGS_KIND_MASK: u64 = 0x03;
+pub(crate) const EXPORT_SYMBOL_FLAGS_KIND_REGULAR: u64 = 0x00;
+pub(crate) const EXPORT_SYMBOL_FLAGS_KIND_THREAD_LOCAL: u64 = 0x01;
+pub(crate) const EXPORT_SYMBOL_FLAGS_KIND_ABSOLUTE: u64 = 0x02;
+pub(crate) const EXPORT_SYMBOL_FLAGS_WEAK_DEFINITION: u64 = 0x04;
+pub(crate) const EXPORT_SYMBOL_FLAGS_REEXPORT: u64 = 0x08;
+pub(crate) const EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER: u64 = 0x10;
+
+// Chained fixups
+pub(crate) const DYLD_CHAINED_IMPORT: u32 = 1;
+pub(crate) const DYLD_CHAINED_IMPORT_ADDEND: u32 = 2;
+pub(crate) const DYLD_CHAINED_IMPORT_ADDEND64: u32 = 3;
+pub(crate) const DYLD_CHAINED_SYMBOL_UNCOMPRESSED: u32 = 0;
+pub(crate) const DYLD_CHAINED_PTR_START_NONE: u16 = 0xFFFF;
+pub(crate) const DYLD_CHAINED_PTR_START_MULTI: u16 = 0x8000;
+pub(crate) const DYLD_CHAINED_PTR_START_LAST: u16 = 0x8000;
+pub(crate) const DYLD_CHAINED_PTR_ARM64E: u16 = 1;
+pub(crate) const DYLD_CHAINED_PTR_64: u16 = 2;
+pub(crate) const DYLD_CHAINED_PTR_32: u16 = 3;
+pub(crate) const DYLD_CHAINED_PTR_32_CACHE: u16 = 4;
+pub(crate) const DYLD_CHAINED_PTR_32_FIRMWARE: u16 = 5;
+pub(crate) const DYLD_CHAINED_PTR_64_OFFSET: u16 = 6;
+pub(crate) const DYLD_CHAINED_PTR_ARM64E_KERNEL: u16 = 7;
+pub(crate) const DYLD_CHAINED_PTR_64_KERNEL_CACHE: u16 = 8;
+pub(crate) const DYLD_CHAINED_PTR_ARM64E_USERLAND: u16 = 9;
+pub(crate) const DYLD_CHAINED_PTR_ARM64E_FIRMWARE: u16 = 10;
+pub(crate) const DYLD_CHAINED_PTR_X86_64_KERNEL_CACHE: u16 = 11;
+pub(crate) const DYLD_CHAINED_PTR_ARM64E_USERLAND24: u16 = 12;
+pub(crate) const DYLD_CHAINED_PTR_ARM64E_SHARED_CACHE: u16 = 13;
+pub(crate) const DYLD_CHAINED_PTR_ARM64E_SEGMENTED: u16 = 14;
+
+// Data in code kinds
+pub(crate) const DICE_KIND_DATA: u16 = 0x0001;
+pub(crate) const DICE_KIND_JUMP_TABLE8: u16 = 0x0002;
+pub(crate) const DICE_KIND_JUMP_TABLE16: u16 = 0x0003;
+pub(crate) const DICE_KIND_JUMP_TABLE32: u16 = 0x0004;
+pub(crate) const DICE_KIND_ABS_JUMP_TABLE32: u16 = 0x0005;
+
+// Code signature blob magics
+pub(crate) const CSMAGIC_REQUIREMENT: u32 = 0xfade0c00;
+pub(crate) const CSMAGIC_REQUIREMENTS: u32 = 0xfade0c01;
+pub(crate) const CSMAGIC_CODEDIRECTORY: u32 = 0xfade0c02;
+pub(crate) const CSMAGIC_EMBEDDED_SIGNATURE: u32 = 0xfade0cc0;
+pub(crate) const CSMAGIC_EMBEDDED_ENTITLEMENTS: u32 = 0xfade7171;
+pub(crate) const CSMAGIC_EMBEDDED_DER_ENTITLEMENTS: u32 = 0xfade7172;
+pub(crate) const CSMAGIC_BLOBWRAPPER: u32 = 0xfade0b01;
+
+// Code signature slots
+pub(crate) const CSSLOT_CODEDIRECTORY: u32 = 0;
+pub(crate) const CSSLOT_INFOSLOT: u32 = 1;
+pub(crate) const CSSLOT_REQUIREMENTS: u32 = 2;
+pub(crate) const CSSLOT_RESOURCEDIR: u32 = 3;
+pub(crate) const CSSLOT_APPLICATION: u32 = 4;
+pub(crate) const CSSLOT_ENTITLEMENTS: u32 = 5;
+pub(crate) const CSSLOT_REP_SPECIFIC: u32 = 6;
+pub(crate) const CSSLOT_DER_ENTITLEMENTS: u32 = 7;
+pub(crate) const CSSLOT_ALTERNATE_CODEDIRECTORIES: u32 = 0x1000;
+pub(crate) const CSSLOT_ALTERNATE_CODEDIRECTORY_LIMIT: u32 = 0x1004;
+pub(crate) const CSSLOT_SIGNATURESLOT: u32 = 0x10000;
+
+// Code directory versions
+pub(crate) const CS_SUPPORTSSCATTER: u32 = 0x20100;
+pub(crate) const CS_SUPPORTSTEAMID: u32 = 0x20200;
+pub(crate) const CS_SUPPORTSCODELIMIT64: u32 = 0x20300;
+pub(crate) const CS_SUPPORTSEXECSEG: u32 = 0x20400;
+pub(crate) const CS_SUPPORTSRUNTIME: u32 = 0x20500;
+
+// Code directory hash types
+pub(crate) const CS_HASHTYPE_SHA1: u8 = 1;
+pub(crate) const CS_HASHTYPE_SHA256: u8 = 2;
+pub(crate) const CS_HASHTYPE_SHA256_TRUNCATED: u8 = 3;
+pub(crate) const CS_HASHTYPE_SHA384: u8 = 4;
+pub(crate) const CS_CDHASH_LEN: usize = 20;
+
+// Code directory flags and layout
+pub(crate) const CS_ADHOC: u32 = 0x2;
+pub(crate) const CS_EXECSEG_MAIN_BINARY: u64 = 0x1;
+pub(crate) const CS_ADHOC_PAGE_SHIFT: u8 = 12;
+pub(crate) const CS_CODEDIRECTORY_EXECSEG_SIZE: u32 = 88;
+
+// Requirement types and forms
+pub(crate) const K_SEC_HOST_REQUIREMENT_TYPE: u32 = 1;
+pub(crate) const K_SEC_GUEST_REQUIREMENT_TYPE: u32 = 2;
+pub(crate) const K_SEC_DESIGNATED_REQUIREMENT_TYPE: u32 = 3;
+pub(crate) const K_SEC_LIBRARY_REQUIREMENT_TYPE: u32 = 4;
+pub(crate) const K_SEC_PLUGIN_REQUIREMENT_TYPE: u32 = 5;
+pub(crate) const K_SEC_EXPRESSION_FORM: u32 = 1;
+
+// Requirement expression opcodes
+pub(crate) const OP_FLAG_MASK: u32 = 0xff000000;
+pub(crate) const OP_FALSE: u32 = 0;
+pub(crate) const OP_TRUE: u32 = 1;
+pub(crate) const OP_IDENT: u32 = 2;
+pub(crate) const OP_APPLE_ANCHOR: u32 = 3;
+pub(crate) const OP_ANCHOR_HASH: u32 = 4;
+pub(crate) const OP_INFO_KEY_VALUE: u32 = 5;
+pub(crate) const OP_AND: u32 = 6;
+pub(crate) const OP_OR: u32 = 7;
+pub(crate) const OP_CD_HASH: u32 = 8;
+pub(crate) const OP_NOT: u32 = 9;
+pub(crate) const OP_INFO_KEY_FIELD: u32 = 10;
+pub(crate) const OP_CERT_FIELD: u32 = 11;
+pub(crate) const OP_TRUSTED_CERT: u32 = 12;
+pub(crate) const OP_TRUSTED_CERTS: u32 = 13;
+pub(crate) const OP_CERT_GENERIC: u32 = 14;
+pub(crate) const OP_APPLE_GENERIC_ANCHOR: u32 = 15;
+pub(crate) const OP_ENTITLEMENT_FIELD: u32 = 16;
+pub(crate) const OP_CERT_POLICY: u32 = 17;
+pub(crate) const OP_NAMED_ANCHOR: u32 = 18;
+pub(crate) const OP_NAMED_CODE: u32 = 19;
+pub(crate) const OP_PLATFORM: u32 = 20;
+pub(crate) const OP_NOTARIZED: u32 = 21;
+pub(crate) const OP_CERT_FIELD_DATE: u32 = 22;
+pub(crate) const OP_LEGACY_DEV_ID: u32 = 23;
+
+// Requirement match operations
+pub(crate) const MATCH_EXISTS: u32 = 0;
+pub(crate) const MATCH_EQUAL: u32 = 1;
+pub(crate) const MATCH_CONTAINS: u32 = 2;
+pub(crate) const MATCH_BEGINS_WITH: u32 = 3;
+pub(crate) const MATCH_ENDS_WITH: u32 = 4;
+pub(crate) const MATCH_LESS_THAN: u32 = 5;
+pub(crate) const MATCH_GREATER_THAN: u32 = 6;
+pub(crate) const MATCH_LESS_EQUAL: u32 = 7;
+pub(crate) const MATCH_GREATER_EQUAL: u32 = 8;
+pub(crate) const MATCH_ON: u32 = 9;
+pub(crate) const MATCH_BEFORE: u32 = 10;
+pub(crate) const MATCH_AFTER: u32 = 11;
+pub(crate) const MATCH_ON_OR_BEFORE: u32 = 12;
+pub(crate) const MATCH_ON_OR_AFTER: u32 = 13;
+pub(crate) const MATCH_ABSENT: u32 = 14;
+
+// Thread state flavors
+pub(crate) const X86_THREAD_STATE32: u32 = 1;
+pub(crate) const X86_FLOAT_STATE32: u32 = 2;
+pub(crate) const X86_EXCEPTION_STATE32: u32 = 3;
+pub(crate) const X86_THREAD_STATE64: u32 = 4;
+pub(crate) const X86_FLOAT_STATE64: u32 = 5;
+pub(crate) const X86_EXCEPTION_STATE64: u32 = 6;
+pub(crate) const X86_THREAD_STATE: u32 = 7;
+pub(crate) const X86_FLOAT_STATE: u32 = 8;
+pub(crate) const X86_EXCEPTION_STATE: u32 = 9;
+pub(crate) const ARM_THREAD_STATE: u32 = 1;
+pub(crate) const ARM_EXCEPTION_STATE: u32 = 3;
+pub(crate) const ARM_THREAD_STATE64: u32 = 6;
+pub(crate) const ARM_EXCEPTION_STATE64: u32 = 7;
+pub(crate) const ARM_NEON_STATE: u32 = 16;
+pub(crate) const ARM_NEON_STATE64: u32 = 17;
+pub(crate) const PPC_THREAD_STATE: u32 = 1;
+pub(crate) const PPC_THREAD_STATE64: u32 = 5;
+
+// Thread state sizes, in 32-bit words
+pub(crate) const X86_THREAD_STATE32_COUNT: u32 = 16;
+pub(crate) const X86_FLOAT_STATE32_COUNT: u32 = 131;
+pub(crate) const X86_EXCEPTION_STATE32_COUNT: u32 = 3;
+pub(crate) const X86_THREAD_STATE64_COUNT: u32 = 42;
+pub(crate) const X86_FLOAT_STATE64_COUNT: u32 = 131;
+pub(crate) const X86_EXCEPTION_STATE64_COUNT: u32 = 4;
+pub(crate) const ARM_THREAD_STATE_COUNT: u32 = 17;
+pub(crate) const ARM_EXCEPTION_STATE_COUNT: u32 = 3;
+pub(crate) const ARM_THREAD_STATE64_COUNT: u32 = 68;
+pub(crate) const ARM_EXCEPTION_STATE64_COUNT: u32 = 4;
+pub(crate) const ARM_NEON_STATE_COUNT: u32 = 68;
+pub(crate) const ARM_NEON_STATE64_COUNT: u32 = 132;
+pub(crate) const PPC_THREAD_STATE_COUNT: u32 = 40;
+pub(crate) const PPC_THREAD_STATE64_COUNT: u32 = 76;
@@ -0,0 +1,136 @@
+use crate::command::LoadCommand;
+use crate::constants;
+use crate::error::Error;
+use crate::extract;
+use crate::extractable;
+use crate::extractor::{read_at, Extractor, Insertable, Inserter};
+use crate::macho::MachO;
+use std::convert::{TryFrom, TryInto};
+use std::io::{Cursor, Read, Seek};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum DataInCodeKind {
+    Data,
+    JumpTable8,
+    JumpTable16,
+    JumpTable32,
+    AbsJumpTable32,
+    Unknown(u16),
+}
+
+impl DataInCodeKind {
+    fn from_u16(v: u16) -> Self {
+        match v {
+            constants::DICE_KIND_DATA => Self::Data,
+            constants::DICE_KIND_JUMP_TABLE8 => Self::JumpTable8,
+            constants::DICE_KIND_JUMP_TABLE16 => Self::JumpTable16,
+            constants::DICE_KIND_JUMP_TABLE32 => Self::JumpTable32,
+            constants::DICE_KIND_ABS_JUMP_TABLE32 => Self::AbsJumpTable32,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+
+    fn to_u16(self) -> u16 {
+        match self {
+            Self::Data => constants::DICE_KIND_DATA,
+            Self::JumpTable8 => constants::DICE_KIND_JUMP_TABLE8,
+            Self::JumpTable16 => constants::DICE_KIND_JUMP_TABLE16,
+            Self::JumpTable32 => constants::DICE_KIND_JUMP_TABLE32,
+            Self::AbsJumpTable32 => constants::DICE_KIND_ABS_JUMP_TABLE32,
+            Self::Unknown(value) => value,
+        }
+    }
+}
+
+impl TryFrom<&mut Extractor<'_>> for DataInCodeKind {
+    type Error = Error;
+
+    fn try_from(e: &mut Extractor) -> Result<Self, Self::Error> {
+        Ok(DataInCodeKind::from_u16(e.try_into()?))
+    }
+}
+
+impl Insertable for DataInCodeKind {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.to_u16().insert_into(i)
+    }
+}
+
+// offsets are file offsets from the start of the mach header
+extractable!(DataInCodeEntry {
+    offset: u32,
+    length: u16,
+    kind: DataInCodeKind,
+});
+
+impl MachO {
+    pub fn data_in_code<R: Read + Seek>(&self, r: &mut R) -> Result<Vec<DataInCodeEntry>, Error> {
+        let command = self.commands.iter().find_map(|command| match command {
+            LoadCommand::DataInCode(command) => Some(command),
+            _ => None,
+        });
+        let command = match command {
+            Some(command) => command,
+            None => return Ok(Vec::new()),
+        };
+        let mut data = Cursor::new(read_at(r, command.dataoff as u64, command.datasize as u64)?);
+        let mut e = self.header.magic.get_extractor(&mut data);
+        let mut entries = Vec::new();
+        for _ in 0..command.datasize / 8 {
+            entries.push(extract!(e));
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries() {
+        let mut data = include_bytes!("../tests/fixtures/osx_launcher_x86_64").to_vec();
+        let mut macho = MachO::from_reader(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(macho.data_in_code(&mut Cursor::new(&data)).unwrap(), []);
+
+        let mut entries = Vec::new();
+        for (offset, length, kind) in [(0xd00u32, 4u16, 2u16), (0xe00, 0x20, 0x100)] {
+            entries.extend_from_slice(&offset.to_le_bytes());
+            entries.extend_from_slice(&length.to_le_bytes());
+            entries.extend_from_slice(&kind.to_le_bytes());
+        }
+        for command in &mut macho.commands {
+            if let LoadCommand::DataInCode(command) = command {
+                command.dataoff = data.len() as u32;
+                // a partial entry at the end is ignored
+                command.datasize = entries.len() as u32 + 4;
+            }
+        }
+        data.extend_from_slice(&entries);
+        data.extend_from_slice(&[0xff; 4]);
+
+        let decoded = macho.data_in_code(&mut Cursor::new(&data)).unwrap();
+        assert_eq!(
+            decoded,
+            [
+                DataInCodeEntry {
+                    offset: 0xd00,
+                    length: 4,
+                    kind: DataInCodeKind::JumpTable8,
+                },
+                DataInCodeEntry {
+                    offset: 0xe00,
+                    length: 0x20,
+                    kind: DataInCodeKind::Unknown(0x100),
+                },
+            ]
+        );
+
+        let mut written = Cursor::new(Vec::new());
+        let mut i = Inserter::little_endian(&mut written);
+        for entry in &decoded {
+            entry.insert_into(&mut i).unwrap();
+        }
+        assert_eq!(written.into_inner(), entries);
+    }
+}
@@ -0,0 +1,240 @@
+use crate::error::Error;
+
+pub(crate) const TAG_BOOLEAN: u8 = 0x01;
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_UTF8_STRING: u8 = 0x0c;
+pub(crate) const TAG_UTC_TIME: u8 = 0x17;
+pub(crate) const TAG_GENERALIZED_TIME: u8 = 0x18;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+pub(crate) const TAG_SET: u8 = 0x31;
+pub(crate) const TAG_CONTEXT_0: u8 = 0xa0;
+pub(crate) const TAG_CONTEXT_1: u8 = 0xa1;
+
+// Apple's DER entitlements wrapper and dictionary tags
+pub(crate) const TAG_ENTITLEMENTS: u8 = 0x70;
+pub(crate) const TAG_DICTIONARY: u8 = 0xb0;
+
+// indefinite-length objects nested deeper than this are rejected
+const MAX_DEPTH: usize = 64;
+
+// a single tag-length-value, with its offset into the outermost buffer
+pub(crate) struct DerObject<'a> {
+    pub tag: u8,
+    pub contents: &'a [u8],
+    pub offset: usize,
+    contents_offset: usize,
+}
+
+impl<'a> DerObject<'a> {
+    pub fn children(&self) -> DerReader<'a> {
+        DerReader {
+            data: self.contents,
+            base: self.contents_offset,
+            position: 0,
+        }
+    }
+
+    pub fn expect(self, tag: u8) -> Result<Self, Error> {
+        if self.tag == tag {
+            Ok(self)
+        } else {
+            Err(Error::InvalidDer(self.offset as u64))
+        }
+    }
+
+    pub fn boolean(&self) -> Result<bool, Error> {
+        match self.contents {
+            [value] => Ok(*value != 0),
+            _ => Err(Error::InvalidDer(self.offset as u64)),
+        }
+    }
+
+    pub fn integer(&self) -> Result<i64, Error> {
+        if self.contents.is_empty() || self.contents.len() > 8 {
+            return Err(Error::InvalidDer(self.offset as u64));
+        }
+        let mut value = if self.contents[0] & 0x80 != 0 { -1 } else { 0 };
+        for byte in self.contents {
+            value = (value << 8) | *byte as i64;
+        }
+        Ok(value)
+    }
+
+    pub fn string(&self) -> Result<String, Error> {
+        Ok(String::from_utf8(self.contents.to_vec())?)
+    }
+
+    // dotted decimal, e.g. 1.2.840.113549.1.7.2
+    pub fn oid(&self) -> Result<String, Error> {
+        let mut arcs = Vec::new();
+        let mut arc = 0u64;
+        for byte in self.contents {
+            if arc > u64::MAX >> 7 {
+                return Err(Error::InvalidDer(self.offset as u64));
+            }
+            arc = (arc << 7) | (byte & 0x7f) as u64;
+            if byte & 0x80 == 0 {
+                arcs.push(arc);
+                arc = 0;
+            }
+        }
+        let first = *arcs.first().ok_or(Error::InvalidDer(self.offset as u64))?;
+        let root = std::cmp::min(first / 40, 2);
+        let mut oid = format!("{}.{}", root, first - root * 40);
+        for arc in &arcs[1..] {
+            oid.push_str(&format!(".{}", arc));
+        }
+        Ok(oid)
+    }
+
+    // UTCTime or GeneralizedTime, normalized to 2020-01-01T00:00:00Z
+    pub fn time(&self) -> Result<String, Error> {
+        let time = self.string()?;
+        if !time.is_ascii() {
+            return Err(Error::InvalidDer(self.offset as u64));
+        }
+        let time = match (self.tag, time.len()) {
+            (TAG_UTC_TIME, 13) => {
+                let century = if &time[0..2] < "50" { "20" } else { "19" };
+                format!("{}{}", century, time)
+            }
+            (TAG_GENERALIZED_TIME, 15) => time,
+            _ => return Err(Error::InvalidDer(self.offset as u64)),
+        };
+        if !time.ends_with('Z') || !time[..14].bytes().all(|c| c.is_ascii_digit()) {
+            return Err(Error::InvalidDer(self.offset as u64));
+        }
+        Ok(format!(
+            "{}-{}-{}T{}:{}:{}Z",
+            &time[0..4],
+            &time[4..6],
+            &time[6..8],
+            &time[8..10],
+            &time[10..12],
+            &time[12..14]
+        ))
+    }
+}
+
+pub(crate) struct DerReader<'a> {
+    data: &'a [u8],
+    base: usize,
+    position: usize,
+}
+
+impl<'a> DerReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            data,
+            base: 0,
+            position: 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn byte(&mut self) -> Result<u8, Error> {
+        let byte = *self
+            .data
+            .get(self.position)
+            .ok_or(Error::InvalidDer((self.base + self.position) as u64))?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    pub fn read(&mut self) -> Result<DerObject<'a>, Error> {
+        self.read_nested(0)
+    }
+
+    // BER allows constructed objects to have an indefinite length, with the contents ending at
+    // an end-of-contents marker; codesign produces these for CMS signatures
+    fn read_nested(&mut self, depth: usize) -> Result<DerObject<'a>, Error> {
+        let offset = self.base + self.position;
+        let tag = self.byte()?;
+        // high tag numbers are never needed for the structures we decode
+        if tag & 0x1f == 0x1f {
+            return Err(Error::InvalidDer(offset as u64));
+        }
+        let length = match self.byte()? {
+            0x80 if tag & 0x20 != 0 && depth < MAX_DEPTH => {
+                let start = self.position;
+                while self.data.get(self.position..self.position + 2) != Some(&[0, 0]) {
+                    self.read_nested(depth + 1)?;
+                }
+                let contents = &self.data[start..self.position];
+                self.position += 2;
+                return Ok(DerObject {
+                    tag,
+                    contents,
+                    offset,
+                    contents_offset: self.base + start,
+                });
+            }
+            short @ 0..=0x7f => short as usize,
+            long @ 0x81..=0x84 => {
+                let mut length = 0usize;
+                for _ in 0..long & 0x7f {
+                    length = (length << 8) | self.byte()? as usize;
+                }
+                length
+            }
+            _ => return Err(Error::InvalidDer(offset as u64)),
+        };
+        let contents = self
+            .data
+            .get(self.position..self.position.saturating_add(length))
+            .ok_or(Error::InvalidDer(offset as u64))?;
+        let contents_offset = self.base + self.position;
+        self.position += length;
+        Ok(DerObject {
+            tag,
+            contents,
+            offset,
+            contents_offset,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indefinite_length() {
+        // a sequence holding an indefinite-length set of one integer, then a boolean
+        let data = [
+            0x30, 0x80, 0x31, 0x80, 0x02, 0x01, 0x05, 0x00, 0x00, 0x01, 0x01, 0xff, 0x00, 0x00,
+        ];
+        let sequence = DerReader::new(&data).read().unwrap();
+        assert_eq!(sequence.contents.len(), 10);
+        let mut children = sequence.children();
+        let mut set = children.read().unwrap().expect(TAG_SET).unwrap().children();
+        let integer = set.read().unwrap();
+        assert_eq!(integer.integer().unwrap(), 5);
+        assert_eq!(integer.offset, 4);
+        assert!(set.is_empty());
+        assert!(children.read().unwrap().boolean().unwrap());
+        assert!(children.is_empty());
+
+        // primitive objects can't use an indefinite length, and the marker must be present
+        assert!(DerReader::new(&[0x04, 0x80, 0x00, 0x00]).read().is_err());
+        assert!(DerReader::new(&[0x30, 0x80, 0x02, 0x01, 0x05])
+            .read()
+            .is_err());
+        assert!(DerReader::new(&[0x30, 0x80].repeat(1000)).read().is_err());
+    }
+
+    #[test]
+    fn non_ascii_time() {
+        // a UTCTime whose second character is multibyte
+        let data = [&[TAG_UTC_TIME, 13, b'0'][..], "é".as_bytes(), b"101000000Z"].concat();
+        assert!(matches!(
+            DerReader::new(&data).read().unwrap().time(),
+            Err(Error::InvalidDer(0))
+        ));
+    }
+}
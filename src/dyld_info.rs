@@ -0,0 +1,485 @@
+use crate::command::{DyldInfoCommand, LoadCommand, SegmentInfo};
+use crate::constants;
+use crate::error::Error;
+use crate::extractor::{read_at, ByteReader};
+use crate::macho::MachO;
+use crate::symbol::LibraryOrdinal;
+use std::io::{Read, Seek};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RebaseType {
+    Pointer,
+    TextAbsolute32,
+    TextPcrel32,
+    Unknown(u8),
+}
+
+impl RebaseType {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            constants::REBASE_TYPE_POINTER => Self::Pointer,
+            constants::REBASE_TYPE_TEXT_ABSOLUTE32 => Self::TextAbsolute32,
+            constants::REBASE_TYPE_TEXT_PCREL32 => Self::TextPcrel32,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Rebase {
+    pub segment_index: u8,
+    pub segment_offset: u64,
+    pub address: u64,
+    pub rebase_type: RebaseType,
+}
+
+// opcode_offset locates the opcode being interpreted, for error reporting
+fn segment_address(
+    segments: &[SegmentInfo],
+    index: u8,
+    offset: u64,
+    opcode_offset: u64,
+) -> Result<u64, Error> {
+    let segment = segments
+        .get(index as usize)
+        .ok_or(Error::InvalidOpcodeSegment {
+            index,
+            offset: opcode_offset,
+        })?;
+    Ok(segment.vmaddr.wrapping_add(offset))
+}
+
+// repeated opcodes can't cover more pointers than fit in the segment
+fn repeat_count(
+    segments: &[SegmentInfo],
+    index: u8,
+    count: u64,
+    pointer_size: u64,
+    offset: u64,
+) -> Result<u64, Error> {
+    let segment = segments
+        .get(index as usize)
+        .ok_or(Error::InvalidOpcodeSegment { index, offset })?;
+    if count > segment.vmsize / pointer_size {
+        return Err(Error::InvalidRepeatCount { count, offset });
+    }
+    Ok(count)
+}
+
+fn decode_rebases(
+    mut opcodes: ByteReader,
+    segments: &[SegmentInfo],
+    pointer_size: u64,
+) -> Result<Vec<Rebase>, Error> {
+    let mut rebases = Vec::new();
+    let mut rebase_type = RebaseType::Pointer;
+    let mut segment_index = 0;
+    let mut segment_offset = 0u64;
+
+    let mut push = |segment_index: u8, segment_offset: u64, rebase_type: RebaseType, offset| {
+        rebases.push(Rebase {
+            segment_index,
+            segment_offset,
+            address: segment_address(segments, segment_index, segment_offset, offset)?,
+            rebase_type,
+        });
+        Ok::<(), Error>(())
+    };
+
+    while !opcodes.is_empty() {
+        let offset = opcodes.offset();
+        let byte = opcodes.read_u8()?;
+        let immediate = byte & constants::REBASE_IMMEDIATE_MASK;
+        match byte & constants::REBASE_OPCODE_MASK {
+            constants::REBASE_OPCODE_DONE => break,
+            constants::REBASE_OPCODE_SET_TYPE_IMM => rebase_type = RebaseType::from_u8(immediate),
+            constants::REBASE_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB => {
+                segment_index = immediate;
+                segment_offset = opcodes.read_uleb128()?;
+            }
+            constants::REBASE_OPCODE_ADD_ADDR_ULEB => {
+                segment_offset = segment_offset.wrapping_add(opcodes.read_uleb128()?);
+            }
+            constants::REBASE_OPCODE_ADD_ADDR_IMM_SCALED => {
+                segment_offset = segment_offset.wrapping_add(immediate as u64 * pointer_size);
+            }
+            constants::REBASE_OPCODE_DO_REBASE_IMM_TIMES => {
+                for _ in 0..immediate {
+                    push(segment_index, segment_offset, rebase_type, offset)?;
+                    segment_offset = segment_offset.wrapping_add(pointer_size);
+                }
+            }
+            constants::REBASE_OPCODE_DO_REBASE_ULEB_TIMES => {
+                let count = opcodes.read_uleb128()?;
+                let count = repeat_count(segments, segment_index, count, pointer_size, offset)?;
+                for _ in 0..count {
+                    push(segment_index, segment_offset, rebase_type, offset)?;
+                    segment_offset = segment_offset.wrapping_add(pointer_size);
+                }
+            }
+            constants::REBASE_OPCODE_DO_REBASE_ADD_ADDR_ULEB => {
+                push(segment_index, segment_offset, rebase_type, offset)?;
+                segment_offset = segment_offset
+                    .wrapping_add(opcodes.read_uleb128()?)
+                    .wrapping_add(pointer_size);
+            }
+            constants::REBASE_OPCODE_DO_REBASE_ULEB_TIMES_SKIPPING_ULEB => {
+                let count = opcodes.read_uleb128()?;
+                let count = repeat_count(segments, segment_index, count, pointer_size, offset)?;
+                let skip = opcodes.read_uleb128()?;
+                for _ in 0..count {
+                    push(segment_index, segment_offset, rebase_type, offset)?;
+                    segment_offset = segment_offset.wrapping_add(skip).wrapping_add(pointer_size);
+                }
+            }
+            _ => {
+                return Err(Error::InvalidOpcode {
+                    opcode: byte,
+                    offset,
+                })
+            }
+        }
+    }
+    Ok(rebases)
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BindType {
+    Pointer,
+    TextAbsolute32,
+    TextPcrel32,
+    Unknown(u8),
+}
+
+impl BindType {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            constants::BIND_TYPE_POINTER => Self::Pointer,
+            constants::BIND_TYPE_TEXT_ABSOLUTE32 => Self::TextAbsolute32,
+            constants::BIND_TYPE_TEXT_PCREL32 => Self::TextPcrel32,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum BindStream {
+    Normal,
+    Weak,
+    Lazy,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Bind {
+    pub segment_index: u8,
+    pub segment_offset: u64,
+    pub address: u64,
+    pub symbol_name: String,
+    pub library_ordinal: LibraryOrdinal,
+    pub addend: i64,
+    pub bind_type: BindType,
+    pub weak_import: bool,
+    pub non_weak_definition: bool,
+}
+
+struct BindState {
+    segment_index: u8,
+    segment_offset: u64,
+    symbol_name: String,
+    library_ordinal: LibraryOrdinal,
+    addend: i64,
+    bind_type: BindType,
+    flags: u8,
+}
+
+impl BindState {
+    fn bind(&self, segments: &[SegmentInfo], offset: u64) -> Result<Bind, Error> {
+        Ok(Bind {
+            segment_index: self.segment_index,
+            segment_offset: self.segment_offset,
+            address: segment_address(segments, self.segment_index, self.segment_offset, offset)?,
+            symbol_name: self.symbol_name.clone(),
+            library_ordinal: self.library_ordinal,
+            addend: self.addend,
+            bind_type: self.bind_type,
+            weak_import: self.flags & constants::BIND_SYMBOL_FLAGS_WEAK_IMPORT != 0,
+            non_weak_definition: self.flags & constants::BIND_SYMBOL_FLAGS_NON_WEAK_DEFINITION != 0,
+        })
+    }
+}
+
+fn special_ordinal(immediate: u8, opcode: u8, offset: u64) -> Result<LibraryOrdinal, Error> {
+    // the immediate is a sign-extended 4-bit value
+    let ordinal = if immediate == 0 {
+        0
+    } else {
+        (immediate | constants::BIND_OPCODE_MASK) as i8
+    };
+    match ordinal {
+        constants::BIND_SPECIAL_DYLIB_SELF => Ok(LibraryOrdinal::SelfImage),
+        constants::BIND_SPECIAL_DYLIB_MAIN_EXECUTABLE => Ok(LibraryOrdinal::MainExecutable),
+        constants::BIND_SPECIAL_DYLIB_FLAT_LOOKUP => Ok(LibraryOrdinal::FlatLookup),
+        constants::BIND_SPECIAL_DYLIB_WEAK_LOOKUP => Ok(LibraryOrdinal::WeakLookup),
+        _ => Err(Error::InvalidOpcode { opcode, offset }),
+    }
+}
+
+fn decode_binds(
+    mut opcodes: ByteReader,
+    segments: &[SegmentInfo],
+    pointer_size: u64,
+    stream: BindStream,
+) -> Result<Vec<Bind>, Error> {
+    let mut binds = Vec::new();
+    let mut state = BindState {
+        segment_index: 0,
+        segment_offset: 0,
+        symbol_name: String::new(),
+        library_ordinal: match stream {
+            BindStream::Weak => LibraryOrdinal::WeakLookup,
+            _ => LibraryOrdinal::SelfImage,
+        },
+        addend: 0,
+        bind_type: BindType::Pointer,
+        flags: 0,
+    };
+
+    while !opcodes.is_empty() {
+        let offset = opcodes.offset();
+        let byte = opcodes.read_u8()?;
+        let immediate = byte & constants::BIND_IMMEDIATE_MASK;
+        match byte & constants::BIND_OPCODE_MASK {
+            // lazy binding info is a sequence of entries, each terminated by DONE
+            constants::BIND_OPCODE_DONE if stream == BindStream::Lazy => {}
+            constants::BIND_OPCODE_DONE => break,
+            constants::BIND_OPCODE_SET_DYLIB_ORDINAL_IMM => {
+                state.library_ordinal = if immediate == 0 {
+                    LibraryOrdinal::SelfImage
+                } else {
+                    LibraryOrdinal::Dylib(immediate as u16)
+                };
+            }
+            constants::BIND_OPCODE_SET_DYLIB_ORDINAL_ULEB => {
+                let ordinal = opcodes.read_uleb128()?;
+                state.library_ordinal = match ordinal {
+                    0 => LibraryOrdinal::SelfImage,
+                    1..=0xffff => LibraryOrdinal::Dylib(ordinal as u16),
+                    _ => {
+                        return Err(Error::InvalidOpcode {
+                            opcode: byte,
+                            offset,
+                        })
+                    }
+                };
+            }
+            constants::BIND_OPCODE_SET_DYLIB_SPECIAL_IMM => {
+                state.library_ordinal = special_ordinal(immediate, byte, offset)?;
+            }
+            constants::BIND_OPCODE_SET_SYMBOL_TRAILING_FLAGS_IMM => {
+                state.flags = immediate;
+                state.symbol_name = opcodes.read_c_string()?;
+            }
+            constants::BIND_OPCODE_SET_TYPE_IMM => state.bind_type = BindType::from_u8(immediate),
+            constants::BIND_OPCODE_SET_ADDEND_SLEB => state.addend = opcodes.read_sleb128()?,
+            constants::BIND_OPCODE_SET_SEGMENT_AND_OFFSET_ULEB => {
+                state.segment_index = immediate;
+                state.segment_offset = opcodes.read_uleb128()?;
+            }
+            constants::BIND_OPCODE_ADD_ADDR_ULEB => {
+                state.segment_offset = state.segment_offset.wrapping_add(opcodes.read_uleb128()?);
+            }
+            constants::BIND_OPCODE_DO_BIND => {
+                binds.push(state.bind(segments, offset)?);
+                state.segment_offset = state.segment_offset.wrapping_add(pointer_size);
+            }
+            constants::BIND_OPCODE_DO_BIND_ADD_ADDR_ULEB => {
+                binds.push(state.bind(segments, offset)?);
+                state.segment_offset = state
+                    .segment_offset
+                    .wrapping_add(opcodes.read_uleb128()?)
+                    .wrapping_add(pointer_size);
+            }
+            constants::BIND_OPCODE_DO_BIND_ADD_ADDR_IMM_SCALED => {
+                binds.push(state.bind(segments, offset)?);
+                state.segment_offset = state
+                    .segment_offset
+                    .wrapping_add((immediate as u64 + 1) * pointer_size);
+            }
+            constants::BIND_OPCODE_DO_BIND_ULEB_TIMES_SKIPPING_ULEB => {
+                let count = opcodes.read_uleb128()?;
+                let count =
+                    repeat_count(segments, state.segment_index, count, pointer_size, offset)?;
+                let skip = opcodes.read_uleb128()?;
+                for _ in 0..count {
+                    binds.push(state.bind(segments, offset)?);
+                    state.segment_offset = state
+                        .segment_offset
+                        .wrapping_add(skip)
+                        .wrapping_add(pointer_size);
+                }
+            }
+            constants::BIND_OPCODE_THREADED => {
+                return Err(Error::UnsupportedOpcode {
+                    opcode: byte,
+                    offset,
+                })
+            }
+            _ => {
+                return Err(Error::InvalidOpcode {
+                    opcode: byte,
+                    offset,
+                })
+            }
+        }
+    }
+    Ok(binds)
+}
+
+impl DyldInfoCommand {
+    pub fn rebases<R: Read + Seek>(&self, macho: &MachO, r: &mut R) -> Result<Vec<Rebase>, Error> {
+        let opcodes = read_at(r, self.rebase_off as u64, self.rebase_size as u64)?;
+        decode_rebases(
+            ByteReader::new(&opcodes, self.rebase_off as u64),
+            &macho.segments(),
+            macho.pointer_size(),
+        )
+    }
+
+    pub fn binds<R: Read + Seek>(
+        &self,
+        stream: BindStream,
+        macho: &MachO,
+        r: &mut R,
+    ) -> Result<Vec<Bind>, Error> {
+        let (offset, size) = match stream {
+            BindStream::Normal => (self.bind_off, self.bind_size),
+            BindStream::Weak => (self.weak_bind_off, self.weak_bind_size),
+            BindStream::Lazy => (self.lazy_bind_off, self.lazy_bind_size),
+        };
+        let opcodes = read_at(r, offset as u64, size as u64)?;
+        decode_binds(
+            ByteReader::new(&opcodes, offset as u64),
+            &macho.segments(),
+            macho.pointer_size(),
+            stream,
+        )
+    }
+}
+
+impl MachO {
+    pub fn dyld_info(&self) -> Option<&DyldInfoCommand> {
+        self.commands.iter().find_map(|command| match command {
+            LoadCommand::DyldInfo(dyld_info) | LoadCommand::DyldInfoOnly(dyld_info) => {
+                Some(dyld_info)
+            }
+            _ => None,
+        })
+    }
+
+    pub fn rebases<R: Read + Seek>(&self, r: &mut R) -> Result<Vec<Rebase>, Error> {
+        match self.dyld_info() {
+            Some(dyld_info) => dyld_info.rebases(self, r),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    pub fn binds<R: Read + Seek>(&self, stream: BindStream, r: &mut R) -> Result<Vec<Bind>, Error> {
+        match self.dyld_info() {
+            Some(dyld_info) => dyld_info.binds(stream, self, r),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments() -> Vec<SegmentInfo<'static>> {
+        vec![SegmentInfo {
+            segname: "__DATA",
+            vmaddr: 0x4000,
+            vmsize: 0x1000,
+            fileoff: 0x4000,
+            filesize: 0x1000,
+            maxprot: 3,
+            initprot: 3,
+            flags: 0,
+        }]
+    }
+
+    #[test]
+    fn rebase_uleb_times() {
+        // set segment 0 offset 0x10, rebase twice, then twice more skipping 8 bytes
+        let opcodes = [0x20, 0x10, 0x60, 0x02, 0x80, 0x02, 0x08, 0x00];
+        let rebases = decode_rebases(ByteReader::new(&opcodes, 0x100), &segments(), 8).unwrap();
+        let addresses: Vec<_> = rebases.iter().map(|rebase| rebase.address).collect();
+        assert_eq!(addresses, [0x4010, 0x4018, 0x4020, 0x4030]);
+    }
+
+    #[test]
+    fn rebase_count_exceeds_segment() {
+        let opcodes = [0x20, 0x00, 0x60, 0x81, 0x04];
+        assert!(matches!(
+            decode_rebases(ByteReader::new(&opcodes, 0x100), &segments(), 8),
+            Err(Error::InvalidRepeatCount {
+                count: 0x201,
+                offset: 0x102
+            })
+        ));
+        let opcodes = [0x20, 0x00, 0x80, 0xff, 0xff, 0xff, 0xff, 0x0f, 0x00];
+        assert!(matches!(
+            decode_rebases(ByteReader::new(&opcodes, 0x100), &segments(), 8),
+            Err(Error::InvalidRepeatCount { offset: 0x102, .. })
+        ));
+    }
+
+    #[test]
+    fn bind_uleb_times_skipping() {
+        // bind _f from dylib 1 at segment 0 offset 0, twice, skipping 8 bytes
+        let opcodes = [
+            0x11, 0x40, b'_', b'f', 0, 0x70, 0x00, 0xc0, 0x02, 0x08, 0x00,
+        ];
+        let binds = decode_binds(
+            ByteReader::new(&opcodes, 0x100),
+            &segments(),
+            8,
+            BindStream::Normal,
+        )
+        .unwrap();
+        let addresses: Vec<_> = binds.iter().map(|bind| bind.address).collect();
+        assert_eq!(addresses, [0x4000, 0x4010]);
+        assert_eq!(binds[0].symbol_name, "_f");
+        assert_eq!(binds[0].library_ordinal, LibraryOrdinal::Dylib(1));
+    }
+
+    #[test]
+    fn bind_errors_carry_opcode_offset() {
+        let opcodes = [0x70, 0x00, 0xc0, 0x81, 0x04, 0x00];
+        assert!(matches!(
+            decode_binds(
+                ByteReader::new(&opcodes, 0x100),
+                &segments(),
+                8,
+                BindStream::Normal
+            ),
+            Err(Error::InvalidRepeatCount {
+                count: 0x201,
+                offset: 0x102
+            })
+        ));
+        let opcodes = [0x71, 0x00, 0x90];
+        assert!(matches!(
+            decode_binds(
+                ByteReader::new(&opcodes, 0x100),
+                &segments(),
+                8,
+                BindStream::Normal
+            ),
+            Err(Error::InvalidOpcodeSegment {
+                index: 1,
+                offset: 0x102
+            })
+        ));
+    }
+}
@@ -0,0 +1,170 @@
+use crate::code_signature::{Blob, CodeSignature};
+use crate::der::{self, DerObject, DerReader};
+use crate::error::Error;
+use crate::macho::MachO;
+use crate::plist;
+use std::collections::BTreeMap;
+use std::io::{Read, Seek};
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Value {
+    Boolean(bool),
+    Integer(i64),
+    String(String),
+    Data(Vec<u8>),
+    // dates are kept in their plist form, e.g. 2020-01-01T00:00:00Z
+    Date(String),
+    Array(Vec<Value>),
+    Dictionary(BTreeMap<String, Value>),
+}
+
+// nesting beyond this is rejected instead of recursing further
+const MAX_DEPTH: usize = 128;
+
+fn der_value(object: DerObject, depth: usize) -> Result<Value, Error> {
+    if depth > MAX_DEPTH {
+        return Err(Error::InvalidDer(object.offset as u64));
+    }
+    Ok(match object.tag {
+        der::TAG_BOOLEAN => Value::Boolean(object.boolean()?),
+        der::TAG_INTEGER => Value::Integer(object.integer()?),
+        der::TAG_UTF8_STRING => Value::String(object.string()?),
+        der::TAG_OCTET_STRING => Value::Data(object.contents.to_vec()),
+        der::TAG_GENERALIZED_TIME => Value::Date(object.time()?),
+        der::TAG_SEQUENCE => {
+            let mut array = Vec::new();
+            let mut children = object.children();
+            while !children.is_empty() {
+                array.push(der_value(children.read()?, depth + 1)?);
+            }
+            Value::Array(array)
+        }
+        der::TAG_DICTIONARY => {
+            // each entry is a sequence of a key and a value
+            let mut dictionary = BTreeMap::new();
+            let mut entries = object.children();
+            while !entries.is_empty() {
+                let mut entry = entries.read()?.expect(der::TAG_SEQUENCE)?.children();
+                let key = entry.read()?.expect(der::TAG_UTF8_STRING)?.string()?;
+                dictionary.insert(key, der_value(entry.read()?, depth + 1)?);
+            }
+            Value::Dictionary(dictionary)
+        }
+        _ => return Err(Error::InvalidDer(object.offset as u64)),
+    })
+}
+
+// the DER form is an application-tagged sequence of a version and the dictionary
+pub(crate) fn decode_der(data: &[u8]) -> Result<Value, Error> {
+    let mut outer = DerReader::new(data)
+        .read()?
+        .expect(der::TAG_ENTITLEMENTS)?
+        .children();
+    let version = outer.read()?.expect(der::TAG_INTEGER)?;
+    if version.integer()? != 1 {
+        return Err(Error::InvalidDer(version.offset as u64));
+    }
+    der_value(outer.read()?.expect(der::TAG_DICTIONARY)?, 0)
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Entitlements {
+    pub xml: Option<Vec<u8>>,
+    pub xml_value: Option<Value>,
+    pub der: Option<Vec<u8>>,
+    pub der_value: Option<Value>,
+}
+
+impl Entitlements {
+    // a signature carrying only one of the encodings is not considered to disagree
+    pub fn encodings_disagree(&self) -> bool {
+        match (&self.xml_value, &self.der_value) {
+            (Some(xml), Some(der)) => xml != der,
+            _ => false,
+        }
+    }
+
+    pub fn value(&self) -> Option<&Value> {
+        self.der_value.as_ref().or(self.xml_value.as_ref())
+    }
+}
+
+impl CodeSignature {
+    pub fn entitlements(&self) -> Result<Option<Entitlements>, Error> {
+        let xml = self.blobs.iter().find_map(|blob| match &blob.blob {
+            Blob::Entitlements(data) => Some(data.clone()),
+            _ => None,
+        });
+        let der = self.blobs.iter().find_map(|blob| match &blob.blob {
+            Blob::DerEntitlements(data) => Some(data.clone()),
+            _ => None,
+        });
+        if xml.is_none() && der.is_none() {
+            return Ok(None);
+        }
+        Ok(Some(Entitlements {
+            xml_value: xml.as_deref().map(plist::parse).transpose()?,
+            der_value: der.as_deref().map(decode_der).transpose()?,
+            xml,
+            der,
+        }))
+    }
+}
+
+impl MachO {
+    pub fn entitlements<R: Read + Seek>(&self, r: &mut R) -> Result<Option<Entitlements>, Error> {
+        match self.code_signature(r)? {
+            Some(signature) => signature.entitlements(),
+            None => Ok(None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+        let mut encoded = vec![tag];
+        let length = (contents.len() as u32).to_be_bytes();
+        let skip = length.iter().take_while(|x| **x == 0).count();
+        if contents.len() < 0x80 {
+            encoded.push(contents.len() as u8);
+        } else {
+            encoded.push(0x80 | (4 - skip) as u8);
+            encoded.extend_from_slice(&length[skip..]);
+        }
+        encoded.extend_from_slice(contents);
+        encoded
+    }
+
+    fn entitlements(value: &[u8]) -> Vec<u8> {
+        let entry = [der(der::TAG_UTF8_STRING, b"key"), value.to_vec()].concat();
+        let dictionary = der(der::TAG_DICTIONARY, &der(der::TAG_SEQUENCE, &entry));
+        let outer = [der(der::TAG_INTEGER, &[1]), dictionary].concat();
+        der(der::TAG_ENTITLEMENTS, &outer)
+    }
+
+    #[test]
+    fn der_entitlements() {
+        let value = der(der::TAG_SEQUENCE, &der(der::TAG_BOOLEAN, &[0xff]));
+        let mut dictionary = BTreeMap::new();
+        dictionary.insert("key".to_string(), Value::Array(vec![Value::Boolean(true)]));
+        assert_eq!(
+            decode_der(&entitlements(&value)).unwrap(),
+            Value::Dictionary(dictionary)
+        );
+    }
+
+    #[test]
+    fn der_nesting_limit() {
+        let mut value = Vec::new();
+        for _ in 0..10_000 {
+            value = der(der::TAG_SEQUENCE, &value);
+        }
+        assert!(matches!(
+            decode_der(&entitlements(&value)),
+            Err(Error::InvalidDer(_))
+        ));
+    }
+}
@@ -7,6 +7,38 @@ pub enum Error {
     InvalidLoadCommandSize(u32),
     BadStringParse(Vec<u8>),
     BadString(String),
+    InvalidArchiveMagic(Vec<u8>),
+    InvalidArchiveHeader(u64),
+    InvalidFatArch(u64),
+    InvalidStringIndex(u32),
+    InvalidSymbolIndex(u32),
+    InvalidSegmentIndex(u32),
+    InvalidOpcode { opcode: u8, offset: u64 },
+    UnsupportedOpcode { opcode: u8, offset: u64 },
+    InvalidRepeatCount { count: u64, offset: u64 },
+    InvalidOpcodeSegment { index: u8, offset: u64 },
+    InvalidLeb128(u64),
+    UnexpectedEndOfData(u64),
+    InvalidExportTrieNode(u64),
+    UnsupportedPointerFormat(u16),
+    UnsupportedImportFormat(u32),
+    UnsupportedSymbolFormat(u32),
+    InvalidBlobMagic(u32),
+    InvalidBlobLength(u64),
+    UnsupportedHashType(u8),
+    InvalidHashSize(u8),
+    InvalidPageSize(u8),
+    MissingSegment(String),
+    InsufficientHeaderPadding { required: u64, available: u64 },
+    OffsetOutOfRange(u64),
+    InvalidDer(u64),
+    InvalidPlist(u64),
+    InvalidRequirementOpcode { op: u32, offset: u64 },
+    UnsupportedRequirementKind(u32),
+    InvalidStringOffset(u32),
+    InvalidThreadState { flavor: u32, count: u32 },
+    UnsupportedVersionMinPlatform(u32),
+    InvalidEntryOffset(u64),
 }
 
 impl std::fmt::Display for Error {
@@ -17,6 +49,111 @@ impl std::fmt::Display for Error {
             Self::InvalidLoadCommandSize(val) => write!(f, "Invalid load command size: {}", val),
             Self::BadStringParse(bytes) => write!(f, "Bad parsed string: {:?}", bytes),
             Self::BadString(string) => write!(f, "Bad string: {:?}", string),
+            Self::InvalidArchiveMagic(bytes) => write!(f, "Invalid archive magic: {:?}", bytes),
+            Self::InvalidArchiveHeader(offset) => {
+                write!(f, "Invalid archive member header at offset {:#x}", offset)
+            }
+            Self::InvalidFatArch(offset) => {
+                write!(f, "Invalid fat arch at offset {:#x}", offset)
+            }
+            Self::InvalidStringIndex(index) => write!(f, "Invalid string table index: {}", index),
+            Self::InvalidSymbolIndex(index) => write!(f, "Invalid symbol index: {}", index),
+            Self::InvalidSegmentIndex(index) => write!(f, "Invalid segment index: {}", index),
+            Self::InvalidOpcode { opcode, offset } => {
+                write!(f, "Invalid opcode {:#x} at offset {:#x}", opcode, offset)
+            }
+            Self::UnsupportedOpcode { opcode, offset } => {
+                write!(
+                    f,
+                    "Unsupported opcode {:#x} at offset {:#x}",
+                    opcode, offset
+                )
+            }
+            Self::InvalidRepeatCount { count, offset } => {
+                write!(
+                    f,
+                    "Invalid repeat count {} for opcode at offset {:#x}",
+                    count, offset
+                )
+            }
+            Self::InvalidOpcodeSegment { index, offset } => {
+                write!(
+                    f,
+                    "Invalid segment index {} for opcode at offset {:#x}",
+                    index, offset
+                )
+            }
+            Self::InvalidLeb128(offset) => {
+                write!(f, "Invalid LEB128 value at offset {:#x}", offset)
+            }
+            Self::UnexpectedEndOfData(offset) => {
+                write!(f, "Unexpected end of data at offset {:#x}", offset)
+            }
+            Self::InvalidExportTrieNode(offset) => {
+                write!(f, "Invalid export trie node at offset {:#x}", offset)
+            }
+            Self::UnsupportedPointerFormat(format) => {
+                write!(f, "Unsupported chained pointer format: {}", format)
+            }
+            Self::UnsupportedImportFormat(format) => {
+                write!(f, "Unsupported chained import format: {}", format)
+            }
+            Self::UnsupportedSymbolFormat(format) => {
+                write!(f, "Unsupported chained symbol format: {}", format)
+            }
+            Self::InvalidBlobMagic(magic) => write!(f, "Invalid blob magic: {:#x}", magic),
+            Self::InvalidBlobLength(offset) => {
+                write!(f, "Invalid blob length at offset {:#x}", offset)
+            }
+            Self::UnsupportedHashType(hash_type) => {
+                write!(f, "Unsupported code directory hash type: {}", hash_type)
+            }
+            Self::InvalidHashSize(size) => write!(f, "Invalid code directory hash size: {}", size),
+            Self::InvalidPageSize(shift) => {
+                write!(f, "Invalid code directory page size: 2^{}", shift)
+            }
+            Self::MissingSegment(name) => write!(f, "Missing segment: {}", name),
+            Self::InsufficientHeaderPadding {
+                required,
+                available,
+            } => write!(
+                f,
+                "Insufficient header padding: {} bytes required, {} available",
+                required, available
+            ),
+            Self::OffsetOutOfRange(value) => {
+                write!(f, "Value {:#x} does not fit in a 32-bit field", value)
+            }
+            Self::InvalidDer(offset) => write!(f, "Invalid DER at offset {:#x}", offset),
+            Self::InvalidPlist(offset) => {
+                write!(f, "Invalid property list at offset {:#x}", offset)
+            }
+            Self::InvalidRequirementOpcode { op, offset } => {
+                write!(
+                    f,
+                    "Invalid requirement opcode {:#x} at offset {:#x}",
+                    op, offset
+                )
+            }
+            Self::UnsupportedRequirementKind(kind) => {
+                write!(f, "Unsupported requirement kind: {}", kind)
+            }
+            Self::InvalidStringOffset(offset) => {
+                write!(f, "Invalid load command string offset: {}", offset)
+            }
+            Self::InvalidThreadState { flavor, count } => {
+                write!(
+                    f,
+                    "Invalid thread state: flavor {} with {} words",
+                    flavor, count
+                )
+            }
+            Self::UnsupportedVersionMinPlatform(platform) => {
+                write!(f, "No version-min load command for platform {}", platform)
+            }
+            Self::InvalidEntryOffset(offset) => {
+                write!(f, "Entry point offset {:#x} is outside __TEXT", offset)
+            }
         }
     }
 }
@@ -0,0 +1,258 @@
+use crate::command::LoadCommand;
+use crate::constants;
+use crate::error::Error;
+use crate::extractor::{read_at, ByteReader};
+use crate::macho::MachO;
+use crate::symbol::LibraryOrdinal;
+use std::collections::HashSet;
+use std::convert::TryFrom;
+use std::io::{Read, Seek};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ExportKind {
+    Regular,
+    ThreadLocal,
+    Absolute,
+    Unknown(u8),
+}
+
+impl ExportKind {
+    fn from_flags(flags: u64) -> Self {
+        match flags & constants::EXPORT_SYMBOL_FLAGS_KIND_MASK {
+            constants::EXPORT_SYMBOL_FLAGS_KIND_REGULAR => Self::Regular,
+            constants::EXPORT_SYMBOL_FLAGS_KIND_THREAD_LOCAL => Self::ThreadLocal,
+            constants::EXPORT_SYMBOL_FLAGS_KIND_ABSOLUTE => Self::Absolute,
+            unknown => Self::Unknown(unknown as u8),
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum ExportData {
+    // the offset is relative to the start of the image, except for absolute exports where it is
+    // the symbol's value
+    Regular {
+        offset: u64,
+    },
+    Reexport {
+        library_ordinal: LibraryOrdinal,
+        imported_name: Option<String>,
+    },
+    StubAndResolver {
+        stub_offset: u64,
+        resolver_offset: u64,
+    },
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Export {
+    pub name: String,
+    pub flags: u64,
+    pub kind: ExportKind,
+    pub weak_definition: bool,
+    pub data: ExportData,
+}
+
+pub struct ExportTrie {
+    data: Vec<u8>,
+    base: u64,
+}
+
+impl ExportTrie {
+    pub fn new(data: Vec<u8>, base: u64) -> Self {
+        Self { data, base }
+    }
+
+    fn node(&self, offset: u64) -> Result<ByteReader<'_>, Error> {
+        if offset as usize >= self.data.len() {
+            return Err(Error::InvalidExportTrieNode(self.base + offset));
+        }
+        let mut node = ByteReader::new(&self.data, self.base);
+        node.position = offset as usize;
+        Ok(node)
+    }
+
+    fn terminal(&self, name: String, node: &mut ByteReader) -> Result<Export, Error> {
+        let flags = node.read_uleb128()?;
+        let data = if flags & constants::EXPORT_SYMBOL_FLAGS_REEXPORT != 0 {
+            let offset = node.offset();
+            let library_ordinal = match node.read_uleb128()? {
+                ordinal @ 1..=0xffff => LibraryOrdinal::Dylib(ordinal as u16),
+                _ => return Err(Error::InvalidExportTrieNode(offset)),
+            };
+            let imported_name = node.read_c_string()?;
+            ExportData::Reexport {
+                library_ordinal,
+                imported_name: if imported_name.is_empty() {
+                    None
+                } else {
+                    Some(imported_name)
+                },
+            }
+        } else if flags & constants::EXPORT_SYMBOL_FLAGS_STUB_AND_RESOLVER != 0 {
+            ExportData::StubAndResolver {
+                stub_offset: node.read_uleb128()?,
+                resolver_offset: node.read_uleb128()?,
+            }
+        } else {
+            ExportData::Regular {
+                offset: node.read_uleb128()?,
+            }
+        };
+        Ok(Export {
+            name,
+            flags,
+            kind: ExportKind::from_flags(flags),
+            weak_definition: flags & constants::EXPORT_SYMBOL_FLAGS_WEAK_DEFINITION != 0,
+            data,
+        })
+    }
+
+    // the position just past a node's terminal information
+    fn children_position(
+        &self,
+        offset: u64,
+        node: &ByteReader,
+        terminal_size: u64,
+    ) -> Result<usize, Error> {
+        usize::try_from(terminal_size)
+            .ok()
+            .and_then(|size| node.position.checked_add(size))
+            .ok_or(Error::InvalidExportTrieNode(self.base + offset))
+    }
+
+    fn children(&self, node: &mut ByteReader) -> Result<Vec<(String, u64)>, Error> {
+        let count = node.read_u8()?;
+        let mut children = Vec::new();
+        for _ in 0..count {
+            let label = node.read_c_string()?;
+            children.push((label, node.read_uleb128()?));
+        }
+        Ok(children)
+    }
+
+    pub fn exports(&self) -> Result<Vec<Export>, Error> {
+        let mut exports = Vec::new();
+        if self.data.is_empty() {
+            return Ok(exports);
+        }
+        let mut visited = HashSet::new();
+        let mut stack = vec![(String::new(), 0u64)];
+        while let Some((prefix, offset)) = stack.pop() {
+            if !visited.insert(offset) {
+                return Err(Error::InvalidExportTrieNode(self.base + offset));
+            }
+            let mut node = self.node(offset)?;
+            let terminal_size = node.read_uleb128()?;
+            let children_position = self.children_position(offset, &node, terminal_size)?;
+            if terminal_size != 0 {
+                exports.push(self.terminal(prefix.clone(), &mut node)?);
+            }
+            node.position = children_position;
+            // push in reverse so that exports are yielded in trie order
+            for (label, child) in self.children(&mut node)?.into_iter().rev() {
+                stack.push((prefix.clone() + &label, child));
+            }
+        }
+        Ok(exports)
+    }
+
+    pub fn lookup(&self, name: &str) -> Result<Option<Export>, Error> {
+        if self.data.is_empty() {
+            return Ok(None);
+        }
+        let mut visited = HashSet::new();
+        let mut offset = 0;
+        let mut remaining = name;
+        loop {
+            if !visited.insert(offset) {
+                return Err(Error::InvalidExportTrieNode(self.base + offset));
+            }
+            let mut node = self.node(offset)?;
+            let terminal_size = node.read_uleb128()?;
+            if remaining.is_empty() {
+                return if terminal_size != 0 {
+                    Ok(Some(self.terminal(name.to_string(), &mut node)?))
+                } else {
+                    Ok(None)
+                };
+            }
+            node.position = self.children_position(offset, &node, terminal_size)?;
+            let next = self
+                .children(&mut node)?
+                .into_iter()
+                .find(|(label, _)| remaining.starts_with(label.as_str()));
+            match next {
+                Some((label, child)) => {
+                    remaining = &remaining[label.len()..];
+                    offset = child;
+                }
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl MachO {
+    pub fn export_trie<R: Read + Seek>(&self, r: &mut R) -> Result<Option<ExportTrie>, Error> {
+        let exports_trie = self.commands.iter().find_map(|command| match command {
+            LoadCommand::DyldExportsTrie(command) => {
+                Some((command.dataoff as u64, command.datasize as u64))
+            }
+            _ => None,
+        });
+        let dyld_info = self
+            .dyld_info()
+            .filter(|dyld_info| dyld_info.export_size != 0)
+            .map(|dyld_info| (dyld_info.export_off as u64, dyld_info.export_size as u64));
+        match exports_trie.or(dyld_info) {
+            Some((offset, size)) => Ok(Some(ExportTrie::new(read_at(r, offset, size)?, offset))),
+            None => Ok(None),
+        }
+    }
+
+    pub fn exports<R: Read + Seek>(&self, r: &mut R) -> Result<Vec<Export>, Error> {
+        match self.export_trie(r)? {
+            Some(trie) => trie.exports(),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exports_and_lookup() {
+        // root with one child "_main", which exports a regular symbol at offset 0x1000
+        let trie = ExportTrie::new(
+            vec![
+                0x00, 0x01, b'_', b'm', b'a', b'i', b'n', 0x00, 0x09, 0x03, 0x00, 0x80, 0x20, 0x00,
+            ],
+            0x100,
+        );
+        let exports = trie.exports().unwrap();
+        assert_eq!(exports.len(), 1);
+        assert_eq!(exports[0].name, "_main");
+        assert_eq!(exports[0].data, ExportData::Regular { offset: 0x1000 });
+        assert_eq!(trie.lookup("_main").unwrap(), exports.into_iter().next());
+        assert_eq!(trie.lookup("_other").unwrap(), None);
+    }
+
+    #[test]
+    fn oversized_terminal() {
+        let trie = ExportTrie::new(
+            vec![0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+            0x100,
+        );
+        assert!(matches!(
+            trie.exports(),
+            Err(Error::InvalidExportTrieNode(0x100))
+        ));
+        assert!(matches!(
+            trie.lookup("_main"),
+            Err(Error::InvalidExportTrieNode(0x100))
+        ));
+    }
+}
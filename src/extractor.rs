@@ -1,7 +1,7 @@
 use crate::error::Error;
-use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt, WriteBytesExt};
 use std::convert::TryFrom;
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, SeekFrom, Write};
 
 pub(crate) enum Endianness {
     Big,
@@ -11,6 +11,9 @@ pub(crate) enum Endianness {
 pub(crate) trait ReadAndSeek: Read + Seek {}
 impl<T: Read + Seek> ReadAndSeek for T {}
 
+pub(crate) trait WriteAndSeek: Write + Seek {}
+impl<T: Write + Seek> WriteAndSeek for T {}
+
 pub(crate) struct Extractor<'a> {
     pub endianness: Endianness,
     pub reader: &'a mut dyn ReadAndSeek,
@@ -20,18 +23,37 @@ impl<'a> Extractor<'a> {
     pub fn little_endian(reader: &'a mut dyn ReadAndSeek) -> Self {
         Self {
             endianness: Endianness::Little,
-            reader: reader,
+            reader,
         }
     }
 
     pub fn big_endian(reader: &'a mut dyn ReadAndSeek) -> Self {
         Self {
             endianness: Endianness::Big,
-            reader: reader,
+            reader,
         }
     }
 }
 
+impl<'a> TryFrom<&mut Extractor<'a>> for u8 {
+    type Error = Error;
+
+    fn try_from(e: &mut Extractor<'a>) -> Result<Self, Self::Error> {
+        Ok(e.reader.read_u8()?)
+    }
+}
+
+impl<'a> TryFrom<&mut Extractor<'a>> for u16 {
+    type Error = Error;
+
+    fn try_from(e: &mut Extractor<'a>) -> Result<Self, Self::Error> {
+        Ok(match e.endianness {
+            Endianness::Big => e.reader.read_u16::<BigEndian>()?,
+            Endianness::Little => e.reader.read_u16::<LittleEndian>()?,
+        })
+    }
+}
+
 impl<'a> TryFrom<&mut Extractor<'a>> for u32 {
     type Error = Error;
 
@@ -65,6 +87,201 @@ impl<'a> TryFrom<&mut Extractor<'a>> for u128 {
     }
 }
 
+impl<'a, T, const N: usize> TryFrom<&mut Extractor<'a>> for [T; N]
+where
+    T: for<'b> TryFrom<&'b mut Extractor<'a>, Error = Error> + Default + Copy,
+{
+    type Error = Error;
+
+    fn try_from(e: &mut Extractor<'a>) -> Result<Self, Self::Error> {
+        let mut array = [T::default(); N];
+        for element in array.iter_mut() {
+            *element = T::try_from(&mut *e)?;
+        }
+        Ok(array)
+    }
+}
+
+pub(crate) fn read_at<R: Read + Seek>(r: &mut R, offset: u64, size: u64) -> Result<Vec<u8>, Error> {
+    r.seek(SeekFrom::Start(offset))?;
+    // sizes come from the file, so only allocate what can actually be read
+    let mut data = Vec::new();
+    r.take(size).read_to_end(&mut data)?;
+    if (data.len() as u64) < size {
+        return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(data)
+}
+
+pub(crate) fn c_string(data: &[u8]) -> Result<String, Error> {
+    Ok(String::from_utf8(
+        data.iter().copied().take_while(|x| *x != 0).collect(),
+    )?)
+}
+
+// cursor over in-memory linkedit data, reporting errors at file offsets
+pub(crate) struct ByteReader<'a> {
+    data: &'a [u8],
+    base: u64,
+    pub position: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(data: &'a [u8], base: u64) -> Self {
+        Self {
+            data,
+            base,
+            position: 0,
+        }
+    }
+
+    pub fn offset(&self) -> u64 {
+        self.base + self.position as u64
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, Error> {
+        let value = *self
+            .data
+            .get(self.position)
+            .ok_or_else(|| Error::UnexpectedEndOfData(self.offset()))?;
+        self.position += 1;
+        Ok(value)
+    }
+
+    pub fn read_uleb128(&mut self) -> Result<u64, Error> {
+        let offset = self.offset();
+        let mut value = 0u64;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            if shift >= 64 || (shift == 63 && byte & 0x7e != 0) {
+                return Err(Error::InvalidLeb128(offset));
+            }
+            value |= ((byte & 0x7f) as u64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    pub fn read_sleb128(&mut self) -> Result<i64, Error> {
+        let offset = self.offset();
+        let mut value = 0i64;
+        let mut shift = 0;
+        loop {
+            let byte = self.read_u8()?;
+            if shift >= 64 {
+                return Err(Error::InvalidLeb128(offset));
+            }
+            value |= ((byte & 0x7f) as i64) << shift;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1i64 << shift;
+                }
+                return Ok(value);
+            }
+        }
+    }
+
+    pub fn read_c_string(&mut self) -> Result<String, Error> {
+        let remaining = &self.data[self.position.min(self.data.len())..];
+        let len = remaining
+            .iter()
+            .position(|x| *x == 0)
+            .ok_or_else(|| Error::UnexpectedEndOfData(self.offset()))?;
+        let string = String::from_utf8(remaining[..len].to_vec())?;
+        self.position += len + 1;
+        Ok(string)
+    }
+}
+
+pub(crate) struct Inserter<'a> {
+    pub endianness: Endianness,
+    pub writer: &'a mut dyn WriteAndSeek,
+}
+
+impl<'a> Inserter<'a> {
+    pub fn little_endian(writer: &'a mut dyn WriteAndSeek) -> Self {
+        Self {
+            endianness: Endianness::Little,
+            writer,
+        }
+    }
+
+    pub fn big_endian(writer: &'a mut dyn WriteAndSeek) -> Self {
+        Self {
+            endianness: Endianness::Big,
+            writer,
+        }
+    }
+}
+
+pub(crate) trait Insertable {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error>;
+}
+
+impl Insertable for u8 {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        i.writer.write_u8(*self)?;
+        Ok(())
+    }
+}
+
+impl Insertable for u16 {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        match i.endianness {
+            Endianness::Big => i.writer.write_u16::<BigEndian>(*self)?,
+            Endianness::Little => i.writer.write_u16::<LittleEndian>(*self)?,
+        }
+        Ok(())
+    }
+}
+
+impl Insertable for u32 {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        match i.endianness {
+            Endianness::Big => i.writer.write_u32::<BigEndian>(*self)?,
+            Endianness::Little => i.writer.write_u32::<LittleEndian>(*self)?,
+        }
+        Ok(())
+    }
+}
+
+impl Insertable for u64 {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        match i.endianness {
+            Endianness::Big => i.writer.write_u64::<BigEndian>(*self)?,
+            Endianness::Little => i.writer.write_u64::<LittleEndian>(*self)?,
+        }
+        Ok(())
+    }
+}
+
+impl Insertable for u128 {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        match i.endianness {
+            Endianness::Big => i.writer.write_u128::<BigEndian>(*self)?,
+            Endianness::Little => i.writer.write_u128::<LittleEndian>(*self)?,
+        }
+        Ok(())
+    }
+}
+
+impl<T: Insertable, const N: usize> Insertable for [T; N] {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        for element in self {
+            element.insert_into(i)?;
+        }
+        Ok(())
+    }
+}
+
 #[macro_export]
 macro_rules! extractable {
     // used for initializing fields that aren't automagically extracted
@@ -73,7 +290,19 @@ macro_rules! extractable {
 
     // used for seeking past padding
     (@pad $extractor:ident) => ();
-    (@pad $extractor:ident $reserved:expr) => ($extractor.reader.seek(SeekFrom::Current($reserved))?);
+    (@pad $extractor:ident $reserved:expr) => (
+        std::io::Seek::seek(&mut $extractor.reader, std::io::SeekFrom::Current($reserved))?
+    );
+
+    // used for inserting fields, skipping those that aren't automagically extracted
+    (@insert $inserter:ident $value:expr) => ( $crate::extractor::Insertable::insert_into($value, $inserter)? );
+    (@insert $inserter:ident $value:expr, $init:expr) => ();
+
+    // used for writing zeroed padding
+    (@insert_pad $inserter:ident) => ();
+    (@insert_pad $inserter:ident $reserved:expr) => (
+        std::io::Write::write_all(&mut $inserter.writer, &vec![0u8; $reserved as usize])?
+    );
 
     // the entrypoint
     ($name:ident {
@@ -89,11 +318,10 @@ macro_rules! extractable {
         }
 
         // create the extraction implementation
-        impl<'a> std::convert::TryFrom<&mut crate::extractor::Extractor<'a>> for $name {
-            type Error = crate::error::Error;
-            fn try_from(e: &mut crate::extractor::Extractor<'a>) -> Result<Self, Self::Error> {
+        impl<'a> std::convert::TryFrom<&mut $crate::extractor::Extractor<'a>> for $name {
+            type Error = $crate::error::Error;
+            fn try_from(e: &mut $crate::extractor::Extractor<'a>) -> Result<Self, Self::Error> {
                 use std::convert::TryInto;
-                use std::io::Seek;
                 // extract the fields
                 let value = Self {
                     $( $field: extractable!(@init e $($init)*) ),*
@@ -103,6 +331,20 @@ macro_rules! extractable {
                 Ok(value)
             }
         }
+
+        // create the insertion implementation
+        impl $crate::extractor::Insertable for $name {
+            fn insert_into(
+                &self,
+                i: &mut $crate::extractor::Inserter,
+            ) -> Result<(), $crate::error::Error> {
+                // insert the fields
+                $( extractable!(@insert i &self.$field $(, $init)*); )*
+                // zero any reserved space
+                extractable!(@insert_pad i $($reserved)*);
+                Ok(())
+            }
+        }
     );
 }
 
@@ -112,3 +354,10 @@ macro_rules! extract {
         (&mut $extractor).try_into()?
     };
 }
+
+#[macro_export]
+macro_rules! insert {
+    ($inserter:ident, $value:expr) => {
+        $crate::extractor::Insertable::insert_into(&$value, &mut $inserter)?
+    };
+}
@@ -0,0 +1,170 @@
+use crate::constants;
+use crate::error::Error;
+use crate::extract;
+use crate::extractor::Extractor;
+use crate::header::{CpuSubType, CpuType};
+use crate::macho::MachO;
+use crate::slice::SliceReader;
+use std::convert::TryInto;
+use std::io::{Read, Seek, SeekFrom};
+
+#[derive(PartialEq, Debug)]
+pub struct FatArch {
+    pub cputype: CpuType,
+    pub cpusubtype: CpuSubType,
+    pub offset: u64,
+    pub size: u64,
+    pub align: u32,
+}
+
+impl FatArch {
+    pub fn reader<'a, R: Read + Seek>(&self, r: &'a mut R) -> Result<SliceReader<'a, R>, Error> {
+        Ok(SliceReader::new(r, self.offset, self.size)?)
+    }
+
+    pub fn parse<R: Read + Seek>(&self, r: &mut R) -> Result<MachO, Error> {
+        MachO::from_reader(&mut self.reader(r)?)
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct FatBinary {
+    pub is_64_bit: bool,
+    pub arches: Vec<FatArch>,
+}
+
+impl FatBinary {
+    pub fn from_reader<R: Read + Seek>(r: &mut R) -> Result<Self, Error> {
+        let start = r.stream_position()?;
+        let end = r.seek(SeekFrom::End(0))?;
+        r.seek(SeekFrom::Start(start))?;
+
+        let mut e = Extractor::big_endian(r);
+        let is_64_bit = match extract!(e) {
+            constants::FAT_MAGIC => false,
+            constants::FAT_MAGIC_64 => true,
+            value => return Err(Error::InvalidMagic(value)),
+        };
+        let nfat_arch: u32 = extract!(e);
+        let mut arches = Vec::new();
+        for _ in 0..nfat_arch {
+            let header_offset = e.reader.stream_position()?;
+            let cputype = extract!(e);
+            let cpusubtype = extract!(e);
+            let (offset, size) = if is_64_bit {
+                (extract!(e), extract!(e))
+            } else {
+                let offset: u32 = extract!(e);
+                let size: u32 = extract!(e);
+                (offset as u64, size as u64)
+            };
+            let align = extract!(e);
+            if is_64_bit {
+                let _reserved: u32 = extract!(e);
+            }
+            if offset
+                .checked_add(size)
+                .is_none_or(|slice_end| slice_end > end)
+            {
+                return Err(Error::InvalidFatArch(header_offset));
+            }
+            arches.push(FatArch {
+                cputype,
+                cpusubtype,
+                offset,
+                size,
+                align,
+            });
+        }
+        Ok(Self { is_64_bit, arches })
+    }
+
+    pub fn parse_all<R: Read + Seek>(&self, r: &mut R) -> Result<Vec<MachO>, Error> {
+        self.arches.iter().map(|arch| arch.parse(r)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const SLICES: [&[u8]; 2] = [
+        include_bytes!("../tests/fixtures/crcmod_i386.so"),
+        include_bytes!("../tests/fixtures/osx_launcher_x86_64"),
+    ];
+
+    fn fat(is_64_bit: bool, slices: &[&[u8]]) -> Vec<u8> {
+        let magic = if is_64_bit {
+            constants::FAT_MAGIC_64
+        } else {
+            constants::FAT_MAGIC
+        };
+        let mut data = Vec::new();
+        data.extend_from_slice(&magic.to_be_bytes());
+        data.extend_from_slice(&(slices.len() as u32).to_be_bytes());
+        let mut offset = 0x1000;
+        for slice in slices {
+            // the cpu type and subtype, converted from the slice's little endian header
+            for word in slice[4..12].chunks(4) {
+                data.extend_from_slice(&u32::from_le_bytes(word.try_into().unwrap()).to_be_bytes());
+            }
+            if is_64_bit {
+                data.extend_from_slice(&(offset as u64).to_be_bytes());
+                data.extend_from_slice(&(slice.len() as u64).to_be_bytes());
+            } else {
+                data.extend_from_slice(&(offset as u32).to_be_bytes());
+                data.extend_from_slice(&(slice.len() as u32).to_be_bytes());
+            }
+            data.extend_from_slice(&12u32.to_be_bytes());
+            if is_64_bit {
+                data.extend_from_slice(&0u32.to_be_bytes());
+            }
+            offset += (slice.len() + 0xfff) & !0xfff;
+        }
+        for slice in slices {
+            data.resize((data.len() + 0xfff) & !0xfff, 0);
+            data.extend_from_slice(slice);
+        }
+        data
+    }
+
+    #[test]
+    fn two_slices() {
+        for is_64_bit in [false, true] {
+            let data = fat(is_64_bit, &SLICES);
+            let mut r = Cursor::new(&data);
+            let binary = FatBinary::from_reader(&mut r).unwrap();
+            assert_eq!(binary.is_64_bit, is_64_bit);
+            assert_eq!(binary.arches.len(), 2);
+            assert_eq!(binary.arches[0].cputype, CpuType::X86);
+            assert_eq!(binary.arches[1].cputype, CpuType::X86_64);
+            assert_eq!(binary.arches[1].offset, 0x4000);
+            assert_eq!(binary.arches[1].align, 12);
+
+            let objects = binary.parse_all(&mut r).unwrap();
+            for (object, slice) in objects.iter().zip(SLICES) {
+                let expected = MachO::from_reader(&mut Cursor::new(slice)).unwrap();
+                assert_eq!(*object, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn slice_past_end() {
+        let mut data = fat(false, &SLICES);
+        data.truncate(data.len() - 1);
+        assert!(matches!(
+            FatBinary::from_reader(&mut Cursor::new(&data)),
+            Err(Error::InvalidFatArch(28))
+        ));
+
+        // an offset that overflows when the size is added
+        let mut data = fat(true, &SLICES);
+        data[8 + 8..8 + 16].copy_from_slice(&u64::MAX.to_be_bytes());
+        assert!(matches!(
+            FatBinary::from_reader(&mut Cursor::new(&data)),
+            Err(Error::InvalidFatArch(8))
+        ));
+    }
+}
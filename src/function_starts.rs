@@ -0,0 +1,83 @@
+use crate::command::LoadCommand;
+use crate::error::Error;
+use crate::extractor::{read_at, ByteReader};
+use crate::macho::MachO;
+use std::io::{Read, Seek};
+
+impl MachO {
+    pub fn function_starts<R: Read + Seek>(&self, r: &mut R) -> Result<Vec<u64>, Error> {
+        let command = self.commands.iter().find_map(|command| match command {
+            LoadCommand::FunctionStarts(command) => Some(command),
+            _ => None,
+        });
+        let command = match command {
+            Some(command) => command,
+            None => return Ok(Vec::new()),
+        };
+        let data = read_at(r, command.dataoff as u64, command.datasize as u64)?;
+        let mut deltas = ByteReader::new(&data, command.dataoff as u64);
+
+        // the first delta is relative to the start of __TEXT, and the list is zero-terminated
+        let mut address = self.segment("__TEXT").map_or(0, |text| text.vmaddr);
+        let mut starts = Vec::new();
+        while !deltas.is_empty() {
+            let delta = deltas.read_uleb128()?;
+            if delta == 0 {
+                break;
+            }
+            address = address.wrapping_add(delta);
+            starts.push(address);
+        }
+        Ok(starts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn function_starts(deltas: &[u8]) -> Result<Vec<u64>, Error> {
+        let mut data = include_bytes!("../tests/fixtures/osx_launcher_x86_64").to_vec();
+        let mut macho = MachO::from_reader(&mut Cursor::new(&data)).unwrap();
+        for command in &mut macho.commands {
+            if let LoadCommand::FunctionStarts(command) = command {
+                command.dataoff = data.len() as u32;
+                command.datasize = deltas.len() as u32;
+            }
+        }
+        data.extend_from_slice(deltas);
+        macho.function_starts(&mut Cursor::new(&data))
+    }
+
+    #[test]
+    fn launcher_function_starts() {
+        let data = include_bytes!("../tests/fixtures/osx_launcher_x86_64");
+        let mut r = Cursor::new(&data[..]);
+        let macho = MachO::from_reader(&mut r).unwrap();
+        assert_eq!(macho.function_starts(&mut r).unwrap(), [0x100000c30]);
+    }
+
+    #[test]
+    fn deltas() {
+        // anything after the terminating zero is ignored
+        assert_eq!(
+            function_starts(&[0xb0, 0x18, 0x10, 0x00, 0x7f]).unwrap(),
+            [0x100000c30, 0x100000c40]
+        );
+        assert_eq!(
+            function_starts(&[0xb0, 0x18, 0x10]).unwrap(),
+            [0x100000c30, 0x100000c40]
+        );
+        assert_eq!(function_starts(&[]).unwrap(), []);
+    }
+
+    #[test]
+    fn truncated_deltas() {
+        let end = include_bytes!("../tests/fixtures/osx_launcher_x86_64").len() as u64;
+        assert!(matches!(
+            function_starts(&[0x10, 0x80]),
+            Err(Error::UnexpectedEndOfData(offset)) if offset == end + 2
+        ));
+    }
+}
@@ -1,10 +1,10 @@
 use crate::constants;
 use crate::error::Error;
-use crate::extract;
-use crate::extractor::Extractor;
+use crate::extractor::{Extractor, Insertable, Inserter};
+use crate::{extract, insert};
 
 use std::convert::{TryFrom, TryInto};
-use std::io::{Read, Seek};
+use std::io::{Read, Seek, Write};
 
 #[derive(PartialEq, Debug)]
 pub enum Magic {
@@ -22,7 +22,14 @@ impl Magic {
         }
     }
 
-    fn from_u32(v: u32) -> Result<Self, Error> {
+    pub(crate) fn get_inserter<'a, W: Write + Seek>(&self, w: &'a mut W) -> Inserter<'a> {
+        match self {
+            Self::LittleEndian | Self::LittleEndian64 => Inserter::little_endian(w),
+            Self::BigEndian | Self::BigEndian64 => Inserter::big_endian(w),
+        }
+    }
+
+    pub(crate) fn from_u32(v: u32) -> Result<Self, Error> {
         match v {
             constants::MH_MAGIC => Ok(Magic::LittleEndian),
             constants::MH_CIGAM => Ok(Magic::BigEndian),
@@ -46,7 +53,7 @@ impl TryFrom<&mut Extractor<'_>> for Magic {
     type Error = Error;
 
     fn try_from(e: &mut Extractor) -> Result<Self, Self::Error> {
-        Ok(Magic::from_u32(e.try_into()?)?)
+        Magic::from_u32(e.try_into()?)
     }
 }
 
@@ -98,6 +105,12 @@ impl TryFrom<&mut Extractor<'_>> for CpuType {
     }
 }
 
+impl Insertable for CpuType {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.to_u32().insert_into(i)
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub enum CpuSubType {
     Multiple,
@@ -134,6 +147,12 @@ impl TryFrom<&mut Extractor<'_>> for CpuSubType {
     }
 }
 
+impl Insertable for CpuSubType {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.to_u32().insert_into(i)
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub enum Filetype {
     Object,
@@ -194,6 +213,12 @@ impl TryFrom<&mut Extractor<'_>> for Filetype {
     }
 }
 
+impl Insertable for Filetype {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.to_u32().insert_into(i)
+    }
+}
+
 #[derive(PartialEq, Debug)]
 pub struct Header {
     pub magic: Magic,
@@ -203,27 +228,55 @@ pub struct Header {
     pub ncmds: u32,
     pub sizeofcmds: u32,
     pub flags: u32,
+    pub reserved: u32,
 }
 
 impl Header {
     pub fn from_reader<R: Read + Seek>(r: &mut R) -> Result<Self, Error> {
         let magic = Magic::from_u32((&mut Extractor::little_endian(r)).try_into()?)?;
         let mut e = magic.get_extractor(r);
-        let header = Self {
-            magic: magic,
+        let mut header = Self {
+            magic,
             cputype: extract!(e),
             cpusubtype: extract!(e),
             filetype: extract!(e),
             ncmds: extract!(e),
             sizeofcmds: extract!(e),
             flags: extract!(e),
+            reserved: 0,
         };
         if header.is_64_bit() {
-            r.seek(std::io::SeekFrom::Current(4))?; // skip reserved field
+            let mut e = header.magic.get_extractor(r);
+            header.reserved = extract!(e);
         }
         Ok(header)
     }
 
+    pub fn write_to<W: Write + Seek>(&self, w: &mut W) -> Result<(), Error> {
+        self.magic
+            .to_u32()
+            .insert_into(&mut Inserter::little_endian(w))?;
+        let mut i = self.magic.get_inserter(w);
+        insert!(i, self.cputype);
+        insert!(i, self.cpusubtype);
+        insert!(i, self.filetype);
+        insert!(i, self.ncmds);
+        insert!(i, self.sizeofcmds);
+        insert!(i, self.flags);
+        if self.is_64_bit() {
+            insert!(i, self.reserved);
+        }
+        Ok(())
+    }
+
+    pub fn size(&self) -> u32 {
+        if self.is_64_bit() {
+            32
+        } else {
+            28
+        }
+    }
+
     pub fn is_32_bit(&self) -> bool {
         self.magic == Magic::LittleEndian || self.magic == Magic::BigEndian
     }
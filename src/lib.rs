@@ -1,10 +1,31 @@
+pub mod archive;
+pub mod chained_fixups;
+pub mod cms;
+pub mod code_signature;
 pub mod command;
 mod constants;
+pub mod data_in_code;
+mod der;
+pub mod dyld_info;
+pub mod entitlements;
 pub mod error;
+pub mod exports;
 mod extractor;
+pub mod fat;
+pub mod function_starts;
 pub mod header;
 pub mod macho;
+pub mod note;
+mod plist;
+pub mod requirement;
+pub mod signing;
+pub mod slice;
+pub mod symbol;
+pub mod thread;
+pub mod version;
+pub use archive::Archive;
 pub use command::LoadCommand;
 pub use error::Error;
+pub use fat::FatBinary;
 pub use header::Header;
 pub use macho::MachO;
@@ -1,7 +1,10 @@
-use crate::command::LoadCommand;
+use crate::command::{DylibCommand, LoadCommand, SectionInfo, SegmentInfo};
 use crate::error::Error;
 use crate::header::Header;
-use std::io::{Read, Seek};
+use crate::symbol::LibraryOrdinal;
+use crate::version::{Platform, Version};
+use std::io::{Read, Seek, Write};
+use std::ops::Range;
 
 #[derive(PartialEq, Debug)]
 pub struct MachO {
@@ -16,9 +19,305 @@ impl MachO {
         for _ in 0..header.ncmds {
             commands.push(LoadCommand::from_reader(&header, r)?);
         }
-        Ok(Self {
-            header: header,
-            commands: commands,
+        Ok(Self { header, commands })
+    }
+
+    pub fn pointer_size(&self) -> u64 {
+        if self.header.is_64_bit() {
+            8
+        } else {
+            4
+        }
+    }
+
+    pub fn segments(&self) -> Vec<SegmentInfo<'_>> {
+        self.commands
+            .iter()
+            .filter_map(|command| match command {
+                LoadCommand::Segment(segment) => Some(segment.into()),
+                LoadCommand::Segment64(segment) => Some(segment.into()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn segment(&self, name: &str) -> Option<SegmentInfo<'_>> {
+        self.segments()
+            .into_iter()
+            .find(|segment| segment.segname == name)
+    }
+
+    pub fn sections(&self) -> Vec<SectionInfo<'_>> {
+        let mut sections = Vec::new();
+        for command in &self.commands {
+            match command {
+                LoadCommand::Segment(segment) => {
+                    sections.extend(segment.sections.iter().map(SectionInfo::from))
+                }
+                LoadCommand::Segment64(segment) => {
+                    sections.extend(segment.sections.iter().map(SectionInfo::from))
+                }
+                _ => {}
+            }
+        }
+        sections
+    }
+
+    // dependent libraries in load order, so dylibs()[n] has library ordinal n + 1
+    pub fn dylibs(&self) -> Vec<&DylibCommand> {
+        self.commands
+            .iter()
+            .filter_map(|command| match command {
+                LoadCommand::LoadDylib(dylib)
+                | LoadCommand::LoadWeakDylib(dylib)
+                | LoadCommand::ReexportDylib(dylib)
+                | LoadCommand::LoadUpwardDylib(dylib)
+                | LoadCommand::LazyLoadDylib(dylib) => Some(dylib),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn dylib(&self, ordinal: LibraryOrdinal) -> Option<&DylibCommand> {
+        match ordinal {
+            LibraryOrdinal::Dylib(ordinal) if ordinal > 0 => {
+                self.dylibs().get(ordinal as usize - 1).copied()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn id_dylib(&self) -> Option<&DylibCommand> {
+        self.commands.iter().find_map(|command| match command {
+            LoadCommand::IdDylib(dylib) => Some(dylib),
+            _ => None,
+        })
+    }
+
+    pub fn rpaths(&self) -> Vec<&str> {
+        self.commands
+            .iter()
+            .filter_map(|command| match command {
+                LoadCommand::Rpath(rpath) => Some(rpath.path.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn dylinker(&self) -> Option<&str> {
+        self.commands.iter().find_map(|command| match command {
+            LoadCommand::LoadDylinker(dylinker) => Some(dylinker.name.as_str()),
+            _ => None,
         })
     }
+
+    pub fn dyld_environment(&self) -> Vec<&str> {
+        self.commands
+            .iter()
+            .filter_map(|command| match command {
+                LoadCommand::DyldEnvironment(environment) => Some(environment.name.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    // LC_MAIN gives a file offset into __TEXT, older executables give the initial thread state
+    pub fn entry_point(&self) -> Result<Option<u64>, Error> {
+        for command in &self.commands {
+            match command {
+                LoadCommand::Main(main) => {
+                    let text = self
+                        .segment("__TEXT")
+                        .ok_or_else(|| Error::MissingSegment("__TEXT".to_string()))?;
+                    let address = main
+                        .entryoff
+                        .checked_sub(text.fileoff)
+                        .filter(|offset| *offset < text.filesize)
+                        .and_then(|offset| text.vmaddr.checked_add(offset))
+                        .ok_or(Error::InvalidEntryOffset(main.entryoff))?;
+                    return Ok(Some(address));
+                }
+                LoadCommand::UnixThread(thread) => return Ok(thread.program_counter()),
+                _ => {}
+            }
+        }
+        Ok(None)
+    }
+
+    // the first platform and minimum OS version, from either LC_BUILD_VERSION or LC_VERSION_MIN_*,
+    // which cannot distinguish simulators from devices
+    pub fn min_os(&self) -> Option<(Platform, Version)> {
+        self.commands.iter().find_map(|command| match command {
+            LoadCommand::BuildVersion(build) => Some((build.platform, build.minos)),
+            LoadCommand::VersionMinMacosx(min) => Some((Platform::MacOS, min.version)),
+            LoadCommand::VersionMinIphoneos(min) => Some((Platform::IOS, min.version)),
+            LoadCommand::VersionMinTvos(min) => Some((Platform::TvOS, min.version)),
+            LoadCommand::VersionMinWatchos(min) => Some((Platform::WatchOS, min.version)),
+            _ => None,
+        })
+    }
+
+    // the encrypted part of each section overlapping the encrypted range, as file offsets
+    pub fn encrypted_sections(&self) -> Vec<(SectionInfo<'_>, Range<u64>)> {
+        let encrypted = self.commands.iter().find_map(|command| match command {
+            LoadCommand::EncryptionInfo(info) if info.cryptid != 0 => {
+                Some(info.cryptoff as u64..info.cryptoff as u64 + info.cryptsize as u64)
+            }
+            LoadCommand::EncryptionInfo64(info) if info.cryptid != 0 => {
+                Some(info.cryptoff as u64..info.cryptoff as u64 + info.cryptsize as u64)
+            }
+            _ => None,
+        });
+        let Some(encrypted) = encrypted else {
+            return Vec::new();
+        };
+        self.sections()
+            .into_iter()
+            .filter(|section| section.offset != 0)
+            .filter_map(|section| {
+                let start = (section.offset as u64).max(encrypted.start);
+                let end = (section.offset as u64)
+                    .saturating_add(section.size)
+                    .min(encrypted.end);
+                (start < end).then_some((section, start..end))
+            })
+            .collect()
+    }
+
+    // free space between the end of the load commands and the first section's data
+    pub fn header_padding(&self) -> u64 {
+        let end_of_commands = self.header.size() as u64 + self.header.sizeofcmds as u64;
+        let first_data = self
+            .sections()
+            .iter()
+            .filter(|section| section.offset != 0 && section.size != 0)
+            .map(|section| section.offset as u64)
+            .min()
+            .or_else(|| {
+                self.segments()
+                    .iter()
+                    .filter(|segment| segment.fileoff != 0 && segment.filesize != 0)
+                    .map(|segment| segment.fileoff)
+                    .min()
+            })
+            .unwrap_or(end_of_commands);
+        first_data.saturating_sub(end_of_commands)
+    }
+
+    pub fn write_to<W: Write + Seek>(&self, w: &mut W) -> Result<(), Error> {
+        self.header.write_to(w)?;
+        for command in &self.commands {
+            command.write_to(&self.header, w)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{EncryptionInfoCommand64, SegmentCommand64};
+    use std::io::Cursor;
+
+    fn launcher() -> MachO {
+        let data = include_bytes!("../tests/fixtures/osx_launcher_x86_64");
+        MachO::from_reader(&mut Cursor::new(&data[..])).unwrap()
+    }
+
+    fn text(macho: &mut MachO) -> &mut SegmentCommand64 {
+        macho
+            .commands
+            .iter_mut()
+            .find_map(|command| match command {
+                LoadCommand::Segment64(segment) if segment.segname.as_str() == "__TEXT" => {
+                    Some(segment)
+                }
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    fn set_entryoff(macho: &mut MachO, entryoff: u64) {
+        for command in &mut macho.commands {
+            if let LoadCommand::Main(main) = command {
+                main.entryoff = entryoff;
+            }
+        }
+    }
+
+    #[test]
+    fn main_entry_point() {
+        let mut macho = launcher();
+        assert_eq!(macho.entry_point().unwrap(), Some(0x100000c30));
+
+        set_entryoff(&mut macho, 0x1000);
+        assert!(matches!(
+            macho.entry_point(),
+            Err(Error::InvalidEntryOffset(0x1000))
+        ));
+
+        set_entryoff(&mut macho, 0xc30);
+        text(&mut macho).fileoff = 0x1000;
+        assert!(matches!(
+            macho.entry_point(),
+            Err(Error::InvalidEntryOffset(0xc30))
+        ));
+
+        text(&mut macho).fileoff = 0;
+        text(&mut macho).vmaddr = u64::MAX - 0x100;
+        assert!(matches!(
+            macho.entry_point(),
+            Err(Error::InvalidEntryOffset(0xc30))
+        ));
+    }
+
+    fn encrypted(macho: &MachO) -> Vec<(&str, Range<u64>)> {
+        macho
+            .encrypted_sections()
+            .into_iter()
+            .map(|(section, range)| (section.sectname, range))
+            .collect()
+    }
+
+    #[test]
+    fn encrypted_sections() {
+        let mut macho = launcher();
+        assert!(encrypted(&macho).is_empty());
+
+        macho
+            .commands
+            .push(LoadCommand::EncryptionInfo64(EncryptionInfoCommand64 {
+                cryptoff: 0xd00,
+                cryptsize: 0x200,
+                cryptid: 0,
+                pad: 0,
+                padding: Vec::new(),
+            }));
+        assert!(encrypted(&macho).is_empty());
+
+        if let Some(LoadCommand::EncryptionInfo64(info)) = macho.commands.last_mut() {
+            info.cryptid = 1;
+        }
+        assert_eq!(
+            encrypted(&macho),
+            [("__text", 0xd00..0xed3), ("__stubs", 0xed4..0xf00)]
+        );
+
+        text(&mut macho).sections[0].size = u64::MAX;
+        assert_eq!(
+            encrypted(&macho),
+            [("__text", 0xd00..0xf00), ("__stubs", 0xed4..0xf00)]
+        );
+    }
+
+    #[test]
+    fn unixthread_entry_point() {
+        let mut state = vec![0u32; 42];
+        state[32] = 0x1f00;
+        let mut words = vec![0xfeedfacf, 0x01000007, 3, 2, 1, 184, 0, 0, 0x5, 184, 4, 42];
+        words.extend(state);
+        let data: Vec<u8> = words.iter().flat_map(|word| word.to_le_bytes()).collect();
+        let macho = MachO::from_reader(&mut Cursor::new(data)).unwrap();
+        assert_eq!(macho.entry_point().unwrap(), Some(0x1f00));
+    }
 }
@@ -0,0 +1,228 @@
+use crate::command::LoadCommand;
+use crate::error::Error;
+use crate::extractor::{c_string, read_at};
+use crate::macho::MachO;
+use crate::version::Platform;
+use std::convert::TryInto;
+use std::io::{Cursor, Read, Seek};
+
+const OWNER_ADDRABLE_BITS: &str = "addrable bits";
+const OWNER_MAIN_BIN_SPEC: &str = "main bin spec";
+const OWNER_LOAD_BINARY: &str = "load binary";
+const OWNER_PROCESS_METADATA: &str = "process metadata";
+
+// version 3 has a single width, which is reported for both halves of the address space
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct AddrableBits {
+    pub version: u32,
+    pub low_memory_addressing_bits: u32,
+    pub high_memory_addressing_bits: u32,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum MainBinaryType {
+    Unspecified,
+    Kernel,
+    UserProcess,
+    Standalone,
+    Unknown(u32),
+}
+
+impl MainBinaryType {
+    fn from_u32(v: u32) -> Self {
+        match v {
+            0 => Self::Unspecified,
+            1 => Self::Kernel,
+            2 => Self::UserProcess,
+            3 => Self::Standalone,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+}
+
+// the address and slide are u64::MAX when unspecified, and slide and platform are only present
+// from version 2
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct MainBinSpec {
+    pub version: u32,
+    pub binary_type: MainBinaryType,
+    pub address: u64,
+    pub slide: Option<u64>,
+    pub uuid: u128,
+    pub log2_pagesize: u32,
+    pub platform: Option<Platform>,
+}
+
+// the load address is u64::MAX and the name empty when unavailable
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct LoadBinary {
+    pub version: u32,
+    pub uuid: u128,
+    pub load_address: u64,
+    pub slide: u64,
+    pub name: String,
+}
+
+#[derive(PartialEq, Debug)]
+pub enum Note {
+    AddrableBits(AddrableBits),
+    MainBinSpec(MainBinSpec),
+    LoadBinary(LoadBinary),
+    // the JSON text, left for the caller to interpret
+    ProcessMetadata(String),
+    Unknown { owner: String, data: Vec<u8> },
+}
+
+impl MachO {
+    fn parse_note(&self, owner: &str, data: &[u8]) -> Result<Option<Note>, Error> {
+        let mut cursor = Cursor::new(data);
+        let mut e = self.header.magic.get_extractor(&mut cursor);
+        let version: u32 = (&mut e).try_into()?;
+        Ok(match (owner, version) {
+            (OWNER_ADDRABLE_BITS, 3) => {
+                let bits: u32 = (&mut e).try_into()?;
+                Some(Note::AddrableBits(AddrableBits {
+                    version,
+                    low_memory_addressing_bits: bits,
+                    high_memory_addressing_bits: bits,
+                }))
+            }
+            (OWNER_ADDRABLE_BITS, 4) => Some(Note::AddrableBits(AddrableBits {
+                version,
+                low_memory_addressing_bits: (&mut e).try_into()?,
+                high_memory_addressing_bits: (&mut e).try_into()?,
+            })),
+            (OWNER_MAIN_BIN_SPEC, 1 | 2) => {
+                let binary_type = MainBinaryType::from_u32((&mut e).try_into()?);
+                let address = (&mut e).try_into()?;
+                let slide = if version > 1 {
+                    Some((&mut e).try_into()?)
+                } else {
+                    None
+                };
+                let uuid = (&mut e).try_into()?;
+                let log2_pagesize = (&mut e).try_into()?;
+                let platform = match version {
+                    1 => 0,
+                    _ => (&mut e).try_into()?,
+                };
+                let platform = (platform != 0).then(|| Platform::from_u32(platform));
+                Some(Note::MainBinSpec(MainBinSpec {
+                    version,
+                    binary_type,
+                    address,
+                    slide,
+                    uuid,
+                    log2_pagesize,
+                    platform,
+                }))
+            }
+            (OWNER_LOAD_BINARY, 1) => {
+                let uuid = (&mut e).try_into()?;
+                let load_address = (&mut e).try_into()?;
+                let slide = (&mut e).try_into()?;
+                let mut name = Vec::new();
+                e.reader.read_to_end(&mut name)?;
+                Some(Note::LoadBinary(LoadBinary {
+                    version,
+                    uuid,
+                    load_address,
+                    slide,
+                    name: c_string(&name)?,
+                }))
+            }
+            _ => None,
+        })
+    }
+
+    pub fn notes<R: Read + Seek>(&self, r: &mut R) -> Result<Vec<Note>, Error> {
+        let mut notes = Vec::new();
+        for command in &self.commands {
+            let command = match command {
+                LoadCommand::Note(command) => command,
+                _ => continue,
+            };
+            let owner = command.data_owner.as_str();
+            let data = read_at(r, command.offset, command.size)?;
+            // notes that fail to parse are left as raw data
+            let note = if owner == OWNER_PROCESS_METADATA {
+                c_string(&data).ok().map(Note::ProcessMetadata)
+            } else {
+                self.parse_note(owner, &data).ok().flatten()
+            };
+            notes.push(note.unwrap_or_else(|| Note::Unknown {
+                owner: owner.to_string(),
+                data,
+            }));
+        }
+        Ok(notes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn core(notes: &[(&str, &[u8])]) -> Vec<u8> {
+        let commands_size = 40 * notes.len() as u32;
+        let mut data = Vec::new();
+        for word in &[
+            0xfeedfacfu32,
+            0x01000007,
+            3,
+            4,
+            notes.len() as u32,
+            commands_size,
+            0,
+            0,
+        ] {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        let mut offset = 32 + commands_size as u64;
+        for (owner, note) in notes {
+            data.extend_from_slice(&0x31u32.to_le_bytes());
+            data.extend_from_slice(&40u32.to_le_bytes());
+            let mut name = owner.as_bytes().to_vec();
+            name.resize(16, 0);
+            data.extend_from_slice(&name);
+            data.extend_from_slice(&offset.to_le_bytes());
+            data.extend_from_slice(&(note.len() as u64).to_le_bytes());
+            offset += note.len() as u64;
+        }
+        for (_, note) in notes {
+            data.extend_from_slice(note);
+        }
+        data
+    }
+
+    #[test]
+    fn truncated_notes() {
+        let addrable_bits = [4u32.to_le_bytes(), 47u32.to_le_bytes()].concat();
+        let data = core(&[
+            (OWNER_ADDRABLE_BITS, &[3, 0, 0, 0, 47, 0, 0, 0]),
+            (OWNER_ADDRABLE_BITS, &addrable_bits),
+            (OWNER_PROCESS_METADATA, &[0xff, 0]),
+        ]);
+        let mut r = Cursor::new(&data);
+        let macho = MachO::from_reader(&mut r).unwrap();
+        let notes = macho.notes(&mut r).unwrap();
+        assert_eq!(
+            notes,
+            [
+                Note::AddrableBits(AddrableBits {
+                    version: 3,
+                    low_memory_addressing_bits: 47,
+                    high_memory_addressing_bits: 47,
+                }),
+                Note::Unknown {
+                    owner: OWNER_ADDRABLE_BITS.to_string(),
+                    data: addrable_bits,
+                },
+                Note::Unknown {
+                    owner: OWNER_PROCESS_METADATA.to_string(),
+                    data: vec![0xff, 0],
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,247 @@
+use crate::entitlements::Value;
+use crate::error::Error;
+use std::collections::BTreeMap;
+
+// deeper documents are rejected rather than risking the stack
+const MAX_DEPTH: usize = 128;
+
+// a minimal XML property list reader, covering the types that appear in entitlements
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+fn unescape(text: &str, offset: usize) -> Result<String, Error> {
+    let mut unescaped = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..]
+            .find(';')
+            .ok_or(Error::InvalidPlist(offset as u64))?;
+        let entity = &rest[start + 1..start + end];
+        let c = match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => {
+                let code = if let Some(hex) = entity.strip_prefix("#x") {
+                    u32::from_str_radix(hex, 16).ok()
+                } else if let Some(decimal) = entity.strip_prefix('#') {
+                    decimal.parse().ok()
+                } else {
+                    None
+                };
+                code.and_then(std::char::from_u32)
+                    .ok_or(Error::InvalidPlist(offset as u64))?
+            }
+        };
+        unescaped.push(c);
+        rest = &rest[start + end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(unescaped)
+}
+
+fn base64(text: &str, offset: usize) -> Result<Vec<u8>, Error> {
+    let mut data = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in text.bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            b'=' => break,
+            c if c.is_ascii_whitespace() => continue,
+            _ => return Err(Error::InvalidPlist(offset as u64)),
+        };
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            data.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(data)
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self) -> Error {
+        Error::InvalidPlist(self.position as u64)
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    // skips whitespace, comments, and XML declarations
+    fn skip(&mut self) -> Result<(), Error> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            let terminator = if trimmed.starts_with("<?") {
+                "?>"
+            } else if trimmed.starts_with("<!--") {
+                "-->"
+            } else if trimmed.starts_with("<!") {
+                ">"
+            } else {
+                return Ok(());
+            };
+            let end = trimmed.find(terminator).ok_or_else(|| self.error())?;
+            self.position += end + terminator.len();
+        }
+    }
+
+    // returns the tag name and whether the element is self-closing
+    fn open_tag(&mut self) -> Result<(&'a str, bool), Error> {
+        self.skip()?;
+        let rest = self.rest();
+        if !rest.starts_with('<') || rest.starts_with("</") {
+            return Err(self.error());
+        }
+        let end = rest.find('>').ok_or_else(|| self.error())?;
+        let tag = &rest[1..end];
+        self.position += end + 1;
+        let (tag, empty) = match tag.strip_suffix('/') {
+            Some(tag) => (tag, true),
+            None => (tag, false),
+        };
+        let name = tag.split_whitespace().next().ok_or_else(|| self.error())?;
+        Ok((name, empty))
+    }
+
+    fn try_close_tag(&mut self, name: &str) -> Result<bool, Error> {
+        self.skip()?;
+        let close = format!("</{}>", name);
+        if self.rest().starts_with(&close) {
+            self.position += close.len();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn text(&mut self, name: &str, empty: bool) -> Result<String, Error> {
+        if empty {
+            return Ok(String::new());
+        }
+        let offset = self.position;
+        let close = format!("</{}>", name);
+        let end = self.rest().find(&close).ok_or_else(|| self.error())?;
+        let text = &self.rest()[..end];
+        self.position += end + close.len();
+        unescape(text, offset)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Value, Error> {
+        if depth > MAX_DEPTH {
+            return Err(self.error());
+        }
+        let (name, empty) = self.open_tag()?;
+        let offset = self.position;
+        Ok(match name {
+            "plist" => {
+                let value = self.value(depth + 1)?;
+                if !self.try_close_tag("plist")? {
+                    return Err(self.error());
+                }
+                value
+            }
+            "dict" => {
+                let mut dictionary = BTreeMap::new();
+                while !empty && !self.try_close_tag("dict")? {
+                    let (key, key_empty) = self.open_tag()?;
+                    if key != "key" {
+                        return Err(self.error());
+                    }
+                    let key = self.text("key", key_empty)?;
+                    dictionary.insert(key, self.value(depth + 1)?);
+                }
+                Value::Dictionary(dictionary)
+            }
+            "array" => {
+                let mut array = Vec::new();
+                while !empty && !self.try_close_tag("array")? {
+                    array.push(self.value(depth + 1)?);
+                }
+                Value::Array(array)
+            }
+            "true" | "false" => {
+                if !empty && !self.try_close_tag(name)? {
+                    return Err(self.error());
+                }
+                Value::Boolean(name == "true")
+            }
+            "string" => Value::String(self.text(name, empty)?),
+            "integer" => Value::Integer(
+                self.text(name, empty)?
+                    .trim()
+                    .parse()
+                    .map_err(|_| Error::InvalidPlist(offset as u64))?,
+            ),
+            "data" => Value::Data(base64(&self.text(name, empty)?, offset)?),
+            "date" => Value::Date(self.text(name, empty)?.trim().to_string()),
+            _ => return Err(Error::InvalidPlist(offset as u64)),
+        })
+    }
+}
+
+pub(crate) fn parse(data: &[u8]) -> Result<Value, Error> {
+    let text = std::str::from_utf8(data)
+        .map_err(|error| Error::InvalidPlist(error.valid_up_to() as u64))?;
+    let mut parser = Parser { text, position: 0 };
+    let value = parser.value(0)?;
+    parser.skip()?;
+    if parser.position != text.len() {
+        return Err(parser.error());
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entitlements() {
+        let plist = br#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE plist PUBLIC "-//Apple//DTD PLIST 1.0//EN" "http://www.apple.com/DTDs/PropertyList-1.0.dtd">
+<plist version="1.0">
+<dict>
+    <key>com.apple.security.app-sandbox</key>
+    <true/>
+    <key>keychain-access-groups</key>
+    <array>
+        <string>ABCDE12345.com.example.&amp;</string>
+    </array>
+</dict>
+</plist>"#;
+        let mut dictionary = BTreeMap::new();
+        dictionary.insert(
+            "com.apple.security.app-sandbox".to_string(),
+            Value::Boolean(true),
+        );
+        dictionary.insert(
+            "keychain-access-groups".to_string(),
+            Value::Array(vec![Value::String("ABCDE12345.com.example.&".to_string())]),
+        );
+        assert_eq!(parse(plist).unwrap(), Value::Dictionary(dictionary));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let nested = |depth| "<array>".repeat(depth) + &"</array>".repeat(depth);
+        assert!(parse(nested(MAX_DEPTH).as_bytes()).is_ok());
+        assert!(matches!(
+            parse(nested(100_000).as_bytes()),
+            Err(Error::InvalidPlist(_))
+        ));
+    }
+}
@@ -0,0 +1,565 @@
+use crate::code_signature::{Blob, CodeSignature, Requirement};
+use crate::constants;
+use crate::error::Error;
+use crate::extractor::Extractor;
+use std::convert::TryInto;
+use std::fmt::{self, Display, Formatter};
+use std::io::{Cursor, SeekFrom};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RequirementType {
+    Host,
+    Guest,
+    Designated,
+    Library,
+    Plugin,
+    Unknown(u32),
+}
+
+impl RequirementType {
+    pub fn from_u32(v: u32) -> Self {
+        match v {
+            constants::K_SEC_HOST_REQUIREMENT_TYPE => Self::Host,
+            constants::K_SEC_GUEST_REQUIREMENT_TYPE => Self::Guest,
+            constants::K_SEC_DESIGNATED_REQUIREMENT_TYPE => Self::Designated,
+            constants::K_SEC_LIBRARY_REQUIREMENT_TYPE => Self::Library,
+            constants::K_SEC_PLUGIN_REQUIREMENT_TYPE => Self::Plugin,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+}
+
+impl Display for RequirementType {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Host => write!(f, "host"),
+            Self::Guest => write!(f, "guest"),
+            Self::Designated => write!(f, "designated"),
+            Self::Library => write!(f, "library"),
+            Self::Plugin => write!(f, "plugin"),
+            Self::Unknown(value) => write!(f, "/*unknown type*/ {}", value),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CertificateSlot {
+    Leaf,
+    Root,
+    Index(i32),
+}
+
+impl CertificateSlot {
+    fn from_i32(v: i32) -> Self {
+        match v {
+            0 => Self::Leaf,
+            -1 => Self::Root,
+            index => Self::Index(index),
+        }
+    }
+}
+
+impl Display for CertificateSlot {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Leaf => write!(f, "leaf"),
+            Self::Root => write!(f, "root"),
+            Self::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
+
+// timestamps are CFAbsoluteTime seconds
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Match {
+    Exists,
+    Absent,
+    Equal(Vec<u8>),
+    Contains(Vec<u8>),
+    BeginsWith(Vec<u8>),
+    EndsWith(Vec<u8>),
+    LessThan(Vec<u8>),
+    GreaterThan(Vec<u8>),
+    LessEqual(Vec<u8>),
+    GreaterEqual(Vec<u8>),
+    On(i64),
+    Before(i64),
+    After(i64),
+    OnOrBefore(i64),
+    OnOrAfter(i64),
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum Expression {
+    False,
+    True,
+    Identifier(Vec<u8>),
+    AppleAnchor,
+    AppleGenericAnchor,
+    AnchorHash {
+        slot: CertificateSlot,
+        hash: Vec<u8>,
+    },
+    InfoKeyValue {
+        key: Vec<u8>,
+        value: Vec<u8>,
+    },
+    And(Box<Expression>, Box<Expression>),
+    Or(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    CdHash(Vec<u8>),
+    InfoKeyField {
+        key: Vec<u8>,
+        matcher: Match,
+    },
+    EntitlementField {
+        key: Vec<u8>,
+        matcher: Match,
+    },
+    CertificateField {
+        slot: CertificateSlot,
+        field: Vec<u8>,
+        matcher: Match,
+    },
+    CertificateGeneric {
+        slot: CertificateSlot,
+        oid: Vec<u8>,
+        matcher: Match,
+    },
+    CertificatePolicy {
+        slot: CertificateSlot,
+        oid: Vec<u8>,
+        matcher: Match,
+    },
+    CertificateFieldDate {
+        slot: CertificateSlot,
+        oid: Vec<u8>,
+        matcher: Match,
+    },
+    TrustedCertificate(CertificateSlot),
+    TrustedCertificates,
+    NamedAnchor(Vec<u8>),
+    NamedCode(Vec<u8>),
+    Platform(u32),
+    Notarized,
+    LegacyDeveloperId,
+}
+
+// and, or and not nested deeper than this are rejected
+const MAX_DEPTH: usize = 256;
+
+struct Decoder<'a> {
+    e: Extractor<'a>,
+    length: u64,
+}
+
+impl Decoder<'_> {
+    fn u32(&mut self) -> Result<u32, Error> {
+        (&mut self.e).try_into()
+    }
+
+    fn i64(&mut self) -> Result<i64, Error> {
+        let value: u64 = (&mut self.e).try_into()?;
+        Ok(value as i64)
+    }
+
+    // length-prefixed, padded to a multiple of four bytes
+    fn data(&mut self) -> Result<Vec<u8>, Error> {
+        let offset = self.e.reader.stream_position()?;
+        let length = self.u32()?;
+        if length as u64 > self.length.saturating_sub(offset + 4) {
+            return Err(Error::UnexpectedEndOfData(offset));
+        }
+        let mut data = vec![0u8; length as usize];
+        self.e.reader.read_exact(&mut data)?;
+        let padding = (4 - length % 4) % 4;
+        self.e.reader.seek(SeekFrom::Current(padding as i64))?;
+        Ok(data)
+    }
+
+    fn slot(&mut self) -> Result<CertificateSlot, Error> {
+        Ok(CertificateSlot::from_i32(self.u32()? as i32))
+    }
+
+    fn matcher(&mut self) -> Result<Match, Error> {
+        let offset = self.e.reader.stream_position()?;
+        Ok(match self.u32()? {
+            constants::MATCH_EXISTS => Match::Exists,
+            constants::MATCH_ABSENT => Match::Absent,
+            constants::MATCH_EQUAL => Match::Equal(self.data()?),
+            constants::MATCH_CONTAINS => Match::Contains(self.data()?),
+            constants::MATCH_BEGINS_WITH => Match::BeginsWith(self.data()?),
+            constants::MATCH_ENDS_WITH => Match::EndsWith(self.data()?),
+            constants::MATCH_LESS_THAN => Match::LessThan(self.data()?),
+            constants::MATCH_GREATER_THAN => Match::GreaterThan(self.data()?),
+            constants::MATCH_LESS_EQUAL => Match::LessEqual(self.data()?),
+            constants::MATCH_GREATER_EQUAL => Match::GreaterEqual(self.data()?),
+            constants::MATCH_ON => Match::On(self.i64()?),
+            constants::MATCH_BEFORE => Match::Before(self.i64()?),
+            constants::MATCH_AFTER => Match::After(self.i64()?),
+            constants::MATCH_ON_OR_BEFORE => Match::OnOrBefore(self.i64()?),
+            constants::MATCH_ON_OR_AFTER => Match::OnOrAfter(self.i64()?),
+            op => return Err(Error::InvalidRequirementOpcode { op, offset }),
+        })
+    }
+
+    fn expression(&mut self, depth: usize) -> Result<Expression, Error> {
+        let offset = self.e.reader.stream_position()?;
+        let op = self.u32()?;
+        if depth > MAX_DEPTH {
+            return Err(Error::InvalidRequirementOpcode { op, offset });
+        }
+        Ok(match op & !constants::OP_FLAG_MASK {
+            constants::OP_FALSE => Expression::False,
+            constants::OP_TRUE => Expression::True,
+            constants::OP_IDENT => Expression::Identifier(self.data()?),
+            constants::OP_APPLE_ANCHOR => Expression::AppleAnchor,
+            constants::OP_ANCHOR_HASH => Expression::AnchorHash {
+                slot: self.slot()?,
+                hash: self.data()?,
+            },
+            constants::OP_INFO_KEY_VALUE => Expression::InfoKeyValue {
+                key: self.data()?,
+                value: self.data()?,
+            },
+            constants::OP_AND => Expression::And(
+                Box::new(self.expression(depth + 1)?),
+                Box::new(self.expression(depth + 1)?),
+            ),
+            constants::OP_OR => Expression::Or(
+                Box::new(self.expression(depth + 1)?),
+                Box::new(self.expression(depth + 1)?),
+            ),
+            constants::OP_CD_HASH => Expression::CdHash(self.data()?),
+            constants::OP_NOT => Expression::Not(Box::new(self.expression(depth + 1)?)),
+            constants::OP_INFO_KEY_FIELD => Expression::InfoKeyField {
+                key: self.data()?,
+                matcher: self.matcher()?,
+            },
+            constants::OP_CERT_FIELD => Expression::CertificateField {
+                slot: self.slot()?,
+                field: self.data()?,
+                matcher: self.matcher()?,
+            },
+            constants::OP_TRUSTED_CERT => Expression::TrustedCertificate(self.slot()?),
+            constants::OP_TRUSTED_CERTS => Expression::TrustedCertificates,
+            constants::OP_CERT_GENERIC => Expression::CertificateGeneric {
+                slot: self.slot()?,
+                oid: self.data()?,
+                matcher: self.matcher()?,
+            },
+            constants::OP_APPLE_GENERIC_ANCHOR => Expression::AppleGenericAnchor,
+            constants::OP_ENTITLEMENT_FIELD => Expression::EntitlementField {
+                key: self.data()?,
+                matcher: self.matcher()?,
+            },
+            constants::OP_CERT_POLICY => Expression::CertificatePolicy {
+                slot: self.slot()?,
+                oid: self.data()?,
+                matcher: self.matcher()?,
+            },
+            constants::OP_NAMED_ANCHOR => Expression::NamedAnchor(self.data()?),
+            constants::OP_NAMED_CODE => Expression::NamedCode(self.data()?),
+            constants::OP_PLATFORM => Expression::Platform(self.u32()?),
+            constants::OP_NOTARIZED => Expression::Notarized,
+            constants::OP_CERT_FIELD_DATE => Expression::CertificateFieldDate {
+                slot: self.slot()?,
+                oid: self.data()?,
+                matcher: self.matcher()?,
+            },
+            constants::OP_LEGACY_DEV_ID => Expression::LegacyDeveloperId,
+            _ => return Err(Error::InvalidRequirementOpcode { op, offset }),
+        })
+    }
+}
+
+impl Requirement {
+    pub fn expression(&self) -> Result<Expression, Error> {
+        if self.kind != constants::K_SEC_EXPRESSION_FORM {
+            return Err(Error::UnsupportedRequirementKind(self.kind));
+        }
+        let mut cursor = Cursor::new(self.expression.as_slice());
+        let mut decoder = Decoder {
+            e: Extractor::big_endian(&mut cursor),
+            length: self.expression.len() as u64,
+        };
+        decoder.expression(0)
+    }
+}
+
+// strings are printed bare when possible, then quoted, then as hex
+fn write_data(f: &mut Formatter, data: &[u8], dot_okay: bool) -> fmt::Result {
+    let simple = !data.is_empty()
+        && !data[0].is_ascii_digit()
+        && data
+            .iter()
+            .all(|c| c.is_ascii_alphanumeric() || (*c == b'.' && dot_okay));
+    let printable = data
+        .iter()
+        .all(|c| c.is_ascii_graphic() || c.is_ascii_whitespace());
+    if simple {
+        write!(f, "{}", String::from_utf8_lossy(data))
+    } else if printable {
+        write!(f, "\"")?;
+        for c in data {
+            if *c == b'"' || *c == b'\\' {
+                write!(f, "\\")?;
+            }
+            write!(f, "{}", *c as char)?;
+        }
+        write!(f, "\"")
+    } else {
+        write_hash(f, data)
+    }
+}
+
+fn write_hash(f: &mut Formatter, data: &[u8]) -> fmt::Result {
+    write!(f, "H\"")?;
+    for byte in data {
+        write!(f, "{:02x}", byte)?;
+    }
+    write!(f, "\"")
+}
+
+fn write_oid(f: &mut Formatter, oid: &[u8]) -> fmt::Result {
+    let mut arcs = Vec::new();
+    let mut arc = 0u64;
+    for byte in oid {
+        arc = (arc << 7) | (byte & 0x7f) as u64;
+        if byte & 0x80 == 0 {
+            arcs.push(arc);
+            arc = 0;
+        }
+    }
+    if let Some(first) = arcs.first().copied() {
+        let root = std::cmp::min(first / 40, 2);
+        write!(f, "{}.{}", root, first - root * 40)?;
+        for arc in &arcs[1..] {
+            write!(f, ".{}", arc)?;
+        }
+    }
+    Ok(())
+}
+
+impl Display for Match {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let (operator, value) = match self {
+            Self::Exists => return write!(f, " /* exists */"),
+            Self::Absent => return write!(f, " absent "),
+            Self::BeginsWith(value) => {
+                write!(f, " = ")?;
+                write_data(f, value, false)?;
+                return write!(f, "*");
+            }
+            Self::EndsWith(value) => {
+                write!(f, " = *")?;
+                return write_data(f, value, false);
+            }
+            Self::On(time) => return write!(f, " = timestamp \"{}\"", time),
+            Self::Before(time) => return write!(f, " < timestamp \"{}\"", time),
+            Self::After(time) => return write!(f, " > timestamp \"{}\"", time),
+            Self::OnOrBefore(time) => return write!(f, " <= timestamp \"{}\"", time),
+            Self::OnOrAfter(time) => return write!(f, " >= timestamp \"{}\"", time),
+            Self::Equal(value) => ("=", value),
+            Self::Contains(value) => ("~", value),
+            Self::LessThan(value) => ("<", value),
+            Self::GreaterThan(value) => (">", value),
+            Self::LessEqual(value) => ("<=", value),
+            Self::GreaterEqual(value) => (">=", value),
+        };
+        write!(f, " {} ", operator)?;
+        write_data(f, value, false)
+    }
+}
+
+// precedence levels, loosest last
+#[derive(PartialEq, PartialOrd, Clone, Copy)]
+enum Level {
+    Primary,
+    And,
+    Or,
+    Top,
+}
+
+impl Expression {
+    fn write(&self, f: &mut Formatter, level: Level) -> fmt::Result {
+        match self {
+            Self::False => write!(f, "never"),
+            Self::True => write!(f, "always"),
+            Self::Identifier(identifier) => {
+                write!(f, "identifier ")?;
+                write_data(f, identifier, false)
+            }
+            Self::AppleAnchor => write!(f, "anchor apple"),
+            Self::AppleGenericAnchor => write!(f, "anchor apple generic"),
+            Self::AnchorHash { slot, hash } => {
+                write!(f, "certificate {} = ", slot)?;
+                write_hash(f, hash)
+            }
+            Self::InfoKeyValue { key, value } => {
+                write!(f, "info[")?;
+                write_data(f, key, true)?;
+                write!(f, "] = ")?;
+                write_data(f, value, false)
+            }
+            Self::And(left, right) | Self::Or(left, right) => {
+                let (operator, inner) = match self {
+                    Self::And(..) => ("and", Level::And),
+                    _ => ("or", Level::Or),
+                };
+                if level < inner {
+                    write!(f, "(")?;
+                }
+                left.write(f, inner)?;
+                write!(f, " {} ", operator)?;
+                right.write(f, inner)?;
+                if level < inner {
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            Self::Not(expression) => {
+                write!(f, "! ")?;
+                expression.write(f, Level::Primary)
+            }
+            Self::CdHash(hash) => {
+                write!(f, "cdhash ")?;
+                write_hash(f, hash)
+            }
+            Self::InfoKeyField { key, matcher } => {
+                write!(f, "info[")?;
+                write_data(f, key, true)?;
+                write!(f, "]{}", matcher)
+            }
+            Self::EntitlementField { key, matcher } => {
+                write!(f, "entitlement[")?;
+                write_data(f, key, true)?;
+                write!(f, "]{}", matcher)
+            }
+            Self::CertificateField {
+                slot,
+                field,
+                matcher,
+            } => {
+                write!(f, "certificate {}[", slot)?;
+                write_data(f, field, true)?;
+                write!(f, "]{}", matcher)
+            }
+            Self::CertificateGeneric { slot, oid, matcher } => {
+                write!(f, "certificate {}[field.", slot)?;
+                write_oid(f, oid)?;
+                write!(f, "]{}", matcher)
+            }
+            Self::CertificatePolicy { slot, oid, matcher } => {
+                write!(f, "certificate {}[policy.", slot)?;
+                write_oid(f, oid)?;
+                write!(f, "]{}", matcher)
+            }
+            Self::CertificateFieldDate { slot, oid, matcher } => {
+                write!(f, "certificate {}[timestamp.", slot)?;
+                write_oid(f, oid)?;
+                write!(f, "]{}", matcher)
+            }
+            Self::TrustedCertificate(slot) => write!(f, "certificate {} trusted", slot),
+            Self::TrustedCertificates => write!(f, "anchor trusted"),
+            Self::NamedAnchor(name) => {
+                write!(f, "anchor apple ")?;
+                write_data(f, name, false)
+            }
+            Self::NamedCode(name) => {
+                write!(f, "(")?;
+                write_data(f, name, false)?;
+                write!(f, ")")
+            }
+            Self::Platform(platform) => write!(f, "platform = {}", platform),
+            Self::Notarized => write!(f, "notarized"),
+            Self::LegacyDeveloperId => write!(f, "legacy"),
+        }
+    }
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.write(f, Level::Top)
+    }
+}
+
+impl CodeSignature {
+    pub fn requirements(&self) -> Result<Vec<(RequirementType, Expression)>, Error> {
+        let mut requirements = Vec::new();
+        for blob in &self.blobs {
+            if let Blob::Requirements(set) = &blob.blob {
+                for (requirement_type, requirement) in set {
+                    requirements.push((
+                        RequirementType::from_u32(*requirement_type),
+                        requirement.expression()?,
+                    ));
+                }
+            }
+        }
+        Ok(requirements)
+    }
+
+    // one "type => expression" line per requirement, as printed by codesign -d -r-
+    pub fn requirements_text(&self) -> Result<String, Error> {
+        Ok(self
+            .requirements()?
+            .iter()
+            .map(|(requirement_type, expression)| {
+                format!("{} => {}\n", requirement_type, expression)
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirement(words: &[u32], data: &[u8]) -> Requirement {
+        let mut expression = Vec::new();
+        for word in words {
+            expression.extend_from_slice(&word.to_be_bytes());
+        }
+        expression.extend_from_slice(data);
+        Requirement {
+            kind: constants::K_SEC_EXPRESSION_FORM,
+            expression,
+        }
+    }
+
+    #[test]
+    fn decode_expression() {
+        let mut expression = requirement(&[constants::OP_AND, constants::OP_IDENT, 11], b"");
+        expression.expression.extend_from_slice(b"com.example\0");
+        expression
+            .expression
+            .extend_from_slice(&constants::OP_APPLE_ANCHOR.to_be_bytes());
+        assert_eq!(
+            expression.expression().unwrap().to_string(),
+            "identifier \"com.example\" and anchor apple"
+        );
+    }
+
+    #[test]
+    fn oversized_data() {
+        let expression = requirement(&[constants::OP_IDENT, u32::MAX], b"abcd");
+        assert!(matches!(
+            expression.expression(),
+            Err(Error::UnexpectedEndOfData(4))
+        ));
+    }
+
+    #[test]
+    fn nesting_limit() {
+        let mut words = vec![constants::OP_NOT; 100_000];
+        words.push(constants::OP_TRUE);
+        assert!(matches!(
+            requirement(&words, b"").expression(),
+            Err(Error::InvalidRequirementOpcode {
+                op: constants::OP_NOT,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,253 @@
+use crate::code_signature::{blob, super_blob, CodeDirectory, HashType, SlotType};
+use crate::command::{LinkeditDataCommand, LoadCommand};
+use crate::constants;
+use crate::error::Error;
+use crate::header::{CpuType, Filetype};
+use crate::macho::MachO;
+use std::convert::TryFrom;
+use std::io::{Cursor, Read, Seek, SeekFrom, Write};
+
+fn align(value: u64, alignment: u64) -> u64 {
+    value.div_ceil(alignment) * alignment
+}
+
+impl MachO {
+    // replaces any existing signature with an ad-hoc one, updating the load commands in place
+    pub fn sign_adhoc<R: Read + Seek, W: Write + Seek>(
+        &mut self,
+        r: &mut R,
+        w: &mut W,
+        identifier: &str,
+        entitlements: Option<&[u8]>,
+    ) -> Result<(), Error> {
+        let linkedit = self
+            .segment("__LINKEDIT")
+            .ok_or_else(|| Error::MissingSegment("__LINKEDIT".to_string()))?;
+        let linkedit_fileoff = linkedit.fileoff;
+        let existing = self.commands.iter().find_map(|command| match command {
+            LoadCommand::CodeSignature(command) => Some(command.dataoff as u64),
+            _ => None,
+        });
+        let code_limit = match existing {
+            Some(dataoff) => dataoff,
+            None => align(linkedit.fileoff + linkedit.filesize, 16),
+        };
+        let (exec_seg_base, exec_seg_limit) = self
+            .segment("__TEXT")
+            .map_or((0, 0), |text| (text.fileoff, text.filesize));
+
+        // size the signature up front, since the page hashes cover the updated load commands
+        let requirements = blob(constants::CSMAGIC_REQUIREMENTS, &0u32.to_be_bytes());
+        let entitlements =
+            entitlements.map(|data| blob(constants::CSMAGIC_EMBEDDED_ENTITLEMENTS, data));
+        let n_special_slots = if entitlements.is_some() {
+            constants::CSSLOT_ENTITLEMENTS
+        } else {
+            constants::CSSLOT_REQUIREMENTS
+        };
+        let page_size = 1u64 << constants::CS_ADHOC_PAGE_SHIFT;
+        let n_code_slots = code_limit.div_ceil(page_size) as u32;
+        let hash_size = 32;
+        let ident_offset = constants::CS_CODEDIRECTORY_EXECSEG_SIZE;
+        let hash_offset = ident_offset + identifier.len() as u32 + 1 + n_special_slots * hash_size;
+        let directory_size = hash_offset + n_code_slots * hash_size;
+        let blob_count = 3 + entitlements.is_some() as u32;
+        let signature_size = 12
+            + 8 * blob_count
+            + directory_size
+            + requirements.len() as u32
+            + entitlements.as_ref().map_or(0, |data| data.len() as u32)
+            + 8;
+        let datasize = align(signature_size as u64, 16);
+
+        // __LINKEDIT grows or shrinks to end at the signature
+        let segment_alignment = match self.header.cputype {
+            CpuType::Arm64 | CpuType::Arm64_32 => 0x4000,
+            _ => 0x1000,
+        };
+        let filesize = code_limit + datasize - linkedit_fileoff;
+        let vmsize = align(filesize, segment_alignment);
+
+        // check everything stored in 32-bit fields before modifying any commands
+        let to_u32 = |value: u64| u32::try_from(value).map_err(|_| Error::OffsetOutOfRange(value));
+        let (filesize_32, vmsize_32) = if self.header.is_32_bit() {
+            (to_u32(filesize)?, to_u32(vmsize)?)
+        } else {
+            (0, 0)
+        };
+        let code_limit_32 = to_u32(code_limit)?;
+
+        // point LC_CODE_SIGNATURE at the new signature, adding the command if necessary
+        let signature_command = LinkeditDataCommand {
+            dataoff: code_limit_32,
+            datasize: to_u32(datasize)?,
+            padding: Vec::new(),
+        };
+        match self.commands.iter_mut().find_map(|command| match command {
+            LoadCommand::CodeSignature(command) => Some(command),
+            _ => None,
+        }) {
+            Some(command) => {
+                command.dataoff = signature_command.dataoff;
+                command.datasize = signature_command.datasize;
+            }
+            None => {
+                let command = LoadCommand::CodeSignature(signature_command);
+                let size = command.size(&self.header)?;
+                let available = self.header_padding();
+                if (size as u64) > available {
+                    return Err(Error::InsufficientHeaderPadding {
+                        required: size as u64,
+                        available,
+                    });
+                }
+                self.header.ncmds += 1;
+                self.header.sizeofcmds += size;
+                self.commands.push(command);
+            }
+        }
+
+        for command in &mut self.commands {
+            match command {
+                LoadCommand::Segment(segment) if segment.segname.as_str() == "__LINKEDIT" => {
+                    segment.filesize = filesize_32;
+                    segment.vmsize = vmsize_32;
+                }
+                LoadCommand::Segment64(segment) if segment.segname.as_str() == "__LINKEDIT" => {
+                    segment.filesize = filesize;
+                    segment.vmsize = vmsize;
+                }
+                _ => {}
+            }
+        }
+
+        // the signed image is the original file up to the signature, with the new load commands
+        let mut image = Vec::new();
+        r.seek(SeekFrom::Start(0))?;
+        r.take(code_limit).read_to_end(&mut image)?;
+        image.resize(code_limit as usize, 0);
+        let mut commands = Cursor::new(Vec::new());
+        self.write_to(&mut commands)?;
+        let commands = commands.into_inner();
+        image[..commands.len()].copy_from_slice(&commands);
+
+        let hash_type = HashType::Sha256;
+        let mut special_slots = vec![vec![0u8; hash_size as usize]; n_special_slots as usize];
+        special_slots[constants::CSSLOT_REQUIREMENTS as usize - 1] =
+            hash_type.digest(&requirements)?;
+        if let Some(entitlements) = &entitlements {
+            special_slots[constants::CSSLOT_ENTITLEMENTS as usize - 1] =
+                hash_type.digest(entitlements)?;
+        }
+        let mut code_slots = Vec::new();
+        for page in image.chunks(page_size as usize) {
+            code_slots.push(hash_type.digest(page)?);
+        }
+
+        let directory = CodeDirectory {
+            version: constants::CS_SUPPORTSEXECSEG,
+            flags: constants::CS_ADHOC,
+            hash_offset,
+            ident_offset,
+            n_special_slots,
+            n_code_slots,
+            code_limit: code_limit_32,
+            hash_size: hash_size as u8,
+            hash_type,
+            platform: 0,
+            page_size: constants::CS_ADHOC_PAGE_SHIFT,
+            scatter_offset: 0,
+            team_offset: 0,
+            code_limit_64: 0,
+            exec_seg_base,
+            exec_seg_limit,
+            exec_seg_flags: if self.header.filetype == Filetype::Execute {
+                constants::CS_EXECSEG_MAIN_BINARY
+            } else {
+                0
+            },
+            runtime: 0,
+            pre_encrypt_offset: 0,
+            identifier: identifier.to_string(),
+            team_id: None,
+            special_slots,
+            code_slots,
+        };
+
+        let mut blobs = vec![
+            (SlotType::CodeDirectory, directory.to_bytes()?),
+            (SlotType::Requirements, requirements),
+        ];
+        if let Some(entitlements) = entitlements {
+            blobs.push((SlotType::Entitlements, entitlements));
+        }
+        blobs.push((
+            SlotType::Signature,
+            blob(constants::CSMAGIC_BLOBWRAPPER, &[]),
+        ));
+        let mut signature = super_blob(&blobs);
+        signature.resize(datasize as usize, 0);
+
+        w.write_all(&image)?;
+        w.write_all(&signature)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sign_and_verify(data: &[u8], entitlements: Option<&[u8]>) {
+        let mut macho = MachO::from_reader(&mut Cursor::new(data)).unwrap();
+        let mut signed = Cursor::new(Vec::new());
+        macho
+            .sign_adhoc(&mut Cursor::new(data), &mut signed, "test", entitlements)
+            .unwrap();
+
+        let signed = signed.into_inner();
+        let mut r = Cursor::new(&signed);
+        let reparsed = MachO::from_reader(&mut r).unwrap();
+        assert_eq!(reparsed, macho);
+        let signature = reparsed.code_signature(&mut r).unwrap().unwrap();
+        assert_eq!(signature.code_directories()[0].identifier, "test");
+        let verifications = signature.verify(&mut r, None).unwrap();
+        assert_eq!(verifications.len(), 1);
+        assert!(verifications[0].is_valid());
+    }
+
+    #[test]
+    fn sign_then_verify() {
+        sign_and_verify(
+            include_bytes!("../tests/fixtures/osx_launcher_x86_64"),
+            None,
+        );
+        sign_and_verify(
+            include_bytes!("../tests/fixtures/crcmod_i386.so"),
+            Some(b"<plist/>"),
+        );
+    }
+
+    #[test]
+    fn linkedit_out_of_range() {
+        let data = include_bytes!("../tests/fixtures/crcmod_i386.so");
+        let mut macho = MachO::from_reader(&mut Cursor::new(&data[..])).unwrap();
+        for command in &mut macho.commands {
+            if let LoadCommand::Segment(segment) = command {
+                if segment.segname.as_str() == "__LINKEDIT" {
+                    segment.fileoff = u32::MAX - 0x100;
+                    segment.filesize = 0x100;
+                }
+            }
+        }
+        let original = MachO::from_reader(&mut Cursor::new(&data[..])).unwrap();
+        let result = macho.sign_adhoc(
+            &mut Cursor::new(&data[..]),
+            &mut Cursor::new(Vec::new()),
+            "test",
+            None,
+        );
+        assert!(matches!(result, Err(Error::OffsetOutOfRange(_))));
+        assert_eq!(macho.commands.len(), original.commands.len());
+    }
+}
@@ -0,0 +1,63 @@
+use std::io::{Read, Result, Seek, SeekFrom};
+
+/// A reader over a window of another reader, with offsets relative to the start of the window.
+pub struct SliceReader<'a, R: Read + Seek> {
+    reader: &'a mut R,
+    start: u64,
+    size: u64,
+    position: u64,
+}
+
+impl<'a, R: Read + Seek> SliceReader<'a, R> {
+    pub fn new(reader: &'a mut R, start: u64, size: u64) -> Result<Self> {
+        reader.seek(SeekFrom::Start(start))?;
+        Ok(Self {
+            reader,
+            start,
+            size,
+            position: 0,
+        })
+    }
+
+    pub fn start(&self) -> u64 {
+        self.start
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+}
+
+impl<R: Read + Seek> Read for SliceReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let remaining = self.size.saturating_sub(self.position);
+        let len = std::cmp::min(buf.len() as u64, remaining) as usize;
+        if len == 0 {
+            return Ok(0);
+        }
+        let read = self.reader.read(&mut buf[..len])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl<R: Read + Seek> Seek for SliceReader<'_, R> {
+    fn seek(&mut self, pos: SeekFrom) -> Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                self.reader.seek(SeekFrom::Start(self.start + position))?;
+                self.position = position;
+                Ok(position)
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative position",
+            )),
+        }
+    }
+}
@@ -0,0 +1,477 @@
+use crate::command::{DysymtabCommand, LoadCommand, SymtabCommand};
+use crate::constants;
+use crate::error::Error;
+use crate::extract;
+use crate::extractor::{c_string, read_at};
+use crate::header::Header;
+use crate::macho::MachO;
+use std::convert::TryInto;
+use std::io::{Cursor, Read, Seek};
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SymbolKind {
+    Undefined,
+    Absolute,
+    Section,
+    Prebound,
+    Indirect,
+    Unknown(u8),
+}
+
+impl SymbolKind {
+    fn from_u8(v: u8) -> Self {
+        match v {
+            constants::N_UNDF => Self::Undefined,
+            constants::N_ABS => Self::Absolute,
+            constants::N_SECT => Self::Section,
+            constants::N_PBUD => Self::Prebound,
+            constants::N_INDR => Self::Indirect,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum SymbolType {
+    Debug(u8),
+    Normal {
+        private_external: bool,
+        kind: SymbolKind,
+        external: bool,
+    },
+}
+
+impl SymbolType {
+    fn from_u8(v: u8) -> Self {
+        if v & constants::N_STAB != 0 {
+            Self::Debug(v)
+        } else {
+            Self::Normal {
+                private_external: v & constants::N_PEXT != 0,
+                kind: SymbolKind::from_u8(v & constants::N_TYPE),
+                external: v & constants::N_EXT != 0,
+            }
+        }
+    }
+
+    pub fn is_debug(&self) -> bool {
+        matches!(self, Self::Debug(_))
+    }
+
+    pub fn is_external(&self) -> bool {
+        matches!(self, Self::Normal { external: true, .. })
+    }
+
+    pub fn is_undefined(&self) -> bool {
+        matches!(
+            self,
+            Self::Normal {
+                kind: SymbolKind::Undefined,
+                ..
+            }
+        )
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum LibraryOrdinal {
+    SelfImage,
+    MainExecutable,
+    FlatLookup,
+    WeakLookup,
+    Dylib(u16),
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct SymbolDescription(pub u16);
+
+impl SymbolDescription {
+    pub fn reference_type(&self) -> u8 {
+        (self.0 & constants::REFERENCE_TYPE) as u8
+    }
+
+    // only meaningful for undefined symbols in two-level namespace images
+    pub fn library_ordinal(&self) -> LibraryOrdinal {
+        match (self.0 >> 8) as u8 {
+            constants::SELF_LIBRARY_ORDINAL => LibraryOrdinal::SelfImage,
+            constants::DYNAMIC_LOOKUP_ORDINAL => LibraryOrdinal::FlatLookup,
+            constants::EXECUTABLE_ORDINAL => LibraryOrdinal::MainExecutable,
+            ordinal => LibraryOrdinal::Dylib(ordinal as u16),
+        }
+    }
+
+    pub fn is_referenced_dynamically(&self) -> bool {
+        self.0 & constants::REFERENCED_DYNAMICALLY != 0
+    }
+
+    pub fn is_no_dead_strip(&self) -> bool {
+        self.0 & constants::N_NO_DEAD_STRIP != 0
+    }
+
+    pub fn is_weak_ref(&self) -> bool {
+        self.0 & constants::N_WEAK_REF != 0
+    }
+
+    pub fn is_weak_def(&self) -> bool {
+        self.0 & constants::N_WEAK_DEF != 0
+    }
+
+    pub fn is_ref_to_weak(&self) -> bool {
+        self.0 & constants::N_REF_TO_WEAK != 0
+    }
+
+    pub fn is_arm_thumb_def(&self) -> bool {
+        self.0 & constants::N_ARM_THUMB_DEF != 0
+    }
+
+    pub fn is_symbol_resolver(&self) -> bool {
+        self.0 & constants::N_SYMBOL_RESOLVER != 0
+    }
+
+    pub fn is_alt_entry(&self) -> bool {
+        self.0 & constants::N_ALT_ENTRY != 0
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct Symbol {
+    pub name: String,
+    pub n_type: SymbolType,
+    pub n_sect: u8,
+    pub n_desc: SymbolDescription,
+    pub n_value: u64,
+}
+
+impl Symbol {
+    pub fn section(&self) -> Option<u8> {
+        if self.n_sect == constants::NO_SECT {
+            None
+        } else {
+            Some(self.n_sect)
+        }
+    }
+}
+
+impl SymtabCommand {
+    pub fn symbols<R: Read + Seek>(
+        &self,
+        header: &Header,
+        r: &mut R,
+    ) -> Result<Vec<Symbol>, Error> {
+        let strings = read_at(r, self.stroff as u64, self.strsize as u64)?;
+        let entry_size = if header.is_64_bit() { 16 } else { 12 };
+        let mut entries = Cursor::new(read_at(
+            r,
+            self.symoff as u64,
+            self.nsyms as u64 * entry_size,
+        )?);
+        let mut e = header.magic.get_extractor(&mut entries);
+
+        let mut symbols = Vec::new();
+        for _ in 0..self.nsyms {
+            let n_strx: u32 = extract!(e);
+            let n_type: u8 = extract!(e);
+            let n_sect = extract!(e);
+            let n_desc: u16 = extract!(e);
+            let n_value = if header.is_64_bit() {
+                extract!(e)
+            } else {
+                let value: u32 = extract!(e);
+                value as u64
+            };
+            let name = strings
+                .get(n_strx as usize..)
+                .ok_or(Error::InvalidStringIndex(n_strx))?;
+            symbols.push(Symbol {
+                name: c_string(name)?,
+                n_type: SymbolType::from_u8(n_type),
+                n_sect,
+                n_desc: SymbolDescription(n_desc),
+                n_value,
+            });
+        }
+        Ok(symbols)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum IndirectSection {
+    SymbolStubs,
+    LazySymbolPointers,
+    NonLazySymbolPointers,
+}
+
+impl IndirectSection {
+    fn from_section_type(v: u8) -> Option<Self> {
+        match v {
+            constants::S_SYMBOL_STUBS => Some(Self::SymbolStubs),
+            constants::S_LAZY_SYMBOL_POINTERS | constants::S_LAZY_DYLIB_SYMBOL_POINTERS => {
+                Some(Self::LazySymbolPointers)
+            }
+            constants::S_NON_LAZY_SYMBOL_POINTERS => Some(Self::NonLazySymbolPointers),
+            _ => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub enum IndirectTarget {
+    Symbol { index: u32, name: String },
+    Local,
+    Absolute,
+    LocalAbsolute,
+}
+
+#[derive(PartialEq, Debug)]
+pub struct IndirectSymbol {
+    pub address: u64,
+    pub section: IndirectSection,
+    pub target: IndirectTarget,
+}
+
+impl DysymtabCommand {
+    pub fn indirect_symbol_table<R: Read + Seek>(
+        &self,
+        header: &Header,
+        r: &mut R,
+    ) -> Result<Vec<u32>, Error> {
+        let mut entries = Cursor::new(read_at(
+            r,
+            self.indirectsymoff as u64,
+            self.nindirectsyms as u64 * 4,
+        )?);
+        let mut e = header.magic.get_extractor(&mut entries);
+        let mut table = Vec::new();
+        for _ in 0..self.nindirectsyms {
+            table.push(extract!(e));
+        }
+        Ok(table)
+    }
+}
+
+impl MachO {
+    pub fn dysymtab(&self) -> Option<&DysymtabCommand> {
+        self.commands.iter().find_map(|command| match command {
+            LoadCommand::Dysymtab(dysymtab) => Some(dysymtab),
+            _ => None,
+        })
+    }
+
+    pub fn indirect_symbols<R: Read + Seek>(
+        &self,
+        r: &mut R,
+    ) -> Result<Vec<IndirectSymbol>, Error> {
+        let dysymtab = match self.dysymtab() {
+            Some(dysymtab) => dysymtab,
+            None => return Ok(Vec::new()),
+        };
+        let table = dysymtab.indirect_symbol_table(&self.header, r)?;
+        let symbols = self.symbols(r)?;
+        let pointer_size = self.pointer_size();
+
+        let mut indirect_symbols = Vec::new();
+        for section in self.sections() {
+            let kind = match IndirectSection::from_section_type(section.section_type()) {
+                Some(kind) => kind,
+                None => continue,
+            };
+            let stride = match kind {
+                IndirectSection::SymbolStubs => section.reserved2 as u64,
+                _ => pointer_size,
+            };
+            if stride == 0 {
+                continue;
+            }
+            for slot in 0..section.size / stride {
+                let table_index = section.reserved1 as u64 + slot;
+                let entry = *table
+                    .get(table_index as usize)
+                    .ok_or(Error::InvalidSymbolIndex(table_index as u32))?;
+                let local = entry & constants::INDIRECT_SYMBOL_LOCAL != 0;
+                let absolute = entry & constants::INDIRECT_SYMBOL_ABS != 0;
+                let target = match (local, absolute) {
+                    (true, true) => IndirectTarget::LocalAbsolute,
+                    (true, false) => IndirectTarget::Local,
+                    (false, true) => IndirectTarget::Absolute,
+                    (false, false) => IndirectTarget::Symbol {
+                        index: entry,
+                        name: symbols
+                            .get(entry as usize)
+                            .ok_or(Error::InvalidSymbolIndex(entry))?
+                            .name
+                            .clone(),
+                    },
+                };
+                indirect_symbols.push(IndirectSymbol {
+                    address: section.addr + slot * stride,
+                    section: kind,
+                    target,
+                });
+            }
+        }
+        Ok(indirect_symbols)
+    }
+
+    pub fn symtab(&self) -> Option<&SymtabCommand> {
+        self.commands.iter().find_map(|command| match command {
+            LoadCommand::Symtab(symtab) => Some(symtab),
+            _ => None,
+        })
+    }
+
+    pub fn symbols<R: Read + Seek>(&self, r: &mut R) -> Result<Vec<Symbol>, Error> {
+        match self.symtab() {
+            Some(symtab) => symtab.symbols(&self.header, r),
+            None => Ok(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LAUNCHER: &[u8] = include_bytes!("../tests/fixtures/osx_launcher_x86_64");
+
+    #[test]
+    fn symbols() {
+        let mut r = Cursor::new(LAUNCHER);
+        let macho = MachO::from_reader(&mut r).unwrap();
+        let symbols = macho.symbols(&mut r).unwrap();
+        assert_eq!(symbols.len(), 13);
+
+        assert_eq!(
+            symbols[1],
+            Symbol {
+                name: "_main".to_string(),
+                n_type: SymbolType::Normal {
+                    private_external: false,
+                    kind: SymbolKind::Section,
+                    external: true,
+                },
+                n_sect: 1,
+                n_desc: SymbolDescription(0),
+                n_value: 0x100000c30,
+            }
+        );
+        assert_eq!(symbols[1].section(), Some(1));
+        assert!(symbols[0].n_desc.is_referenced_dynamically());
+
+        let fork = &symbols[9];
+        assert_eq!(fork.name, "_fork");
+        assert!(fork.n_type.is_undefined() && fork.n_type.is_external());
+        assert_eq!(fork.section(), None);
+        assert_eq!(fork.n_desc.library_ordinal(), LibraryOrdinal::Dylib(1));
+        assert!(!fork.n_desc.is_weak_ref());
+    }
+
+    #[test]
+    fn invalid_string_index() {
+        let mut data = LAUNCHER.to_vec();
+        // the first symbol's n_strx, just past the end of the string table
+        data[0x2110..0x2114].copy_from_slice(&0xa9u32.to_le_bytes());
+        let mut r = Cursor::new(&data);
+        let macho = MachO::from_reader(&mut r).unwrap();
+        assert!(matches!(
+            macho.symbols(&mut r),
+            Err(Error::InvalidStringIndex(0xa9))
+        ));
+    }
+
+    #[test]
+    fn symbol_fields() {
+        assert_eq!(SymbolType::from_u8(0x24), SymbolType::Debug(0x24));
+        assert!(SymbolType::from_u8(0x24).is_debug());
+        assert_eq!(
+            SymbolType::from_u8(0x1f),
+            SymbolType::Normal {
+                private_external: true,
+                kind: SymbolKind::Section,
+                external: true,
+            }
+        );
+        assert_eq!(
+            SymbolType::from_u8(0x0c),
+            SymbolType::Normal {
+                private_external: false,
+                kind: SymbolKind::Prebound,
+                external: false,
+            }
+        );
+        assert_eq!(SymbolKind::from_u8(0x06), SymbolKind::Unknown(0x06));
+
+        let ordinal = |desc| SymbolDescription(desc).library_ordinal();
+        assert_eq!(ordinal(0x0000), LibraryOrdinal::SelfImage);
+        assert_eq!(ordinal(0x0300), LibraryOrdinal::Dylib(3));
+        assert_eq!(ordinal(0xfe00), LibraryOrdinal::FlatLookup);
+        assert_eq!(ordinal(0xff00), LibraryOrdinal::MainExecutable);
+
+        let desc = SymbolDescription(0x0140);
+        assert_eq!(desc.reference_type(), 0);
+        assert!(desc.is_weak_ref() && !desc.is_weak_def());
+        assert_eq!(desc.library_ordinal(), LibraryOrdinal::Dylib(1));
+    }
+
+    #[test]
+    fn indirect_symbols() {
+        let mut data = LAUNCHER.to_vec();
+        // mark the first two lazy pointers local, and local and absolute
+        let table = 0x21e0 + 12 * 4;
+        data[table..table + 4].copy_from_slice(&0x8000_0000u32.to_le_bytes());
+        data[table + 4..table + 8].copy_from_slice(&0xc000_0000u32.to_le_bytes());
+        let mut r = Cursor::new(&data);
+        let macho = MachO::from_reader(&mut r).unwrap();
+        let symbols = macho.indirect_symbols(&mut r).unwrap();
+        assert_eq!(symbols.len(), 20);
+
+        let stubs: Vec<_> = symbols
+            .iter()
+            .filter(|symbol| symbol.section == IndirectSection::SymbolStubs)
+            .collect();
+        assert_eq!(stubs.len(), 8);
+        assert_eq!(stubs[1].address, 0x100000eda);
+        assert_eq!(
+            stubs[1].target,
+            IndirectTarget::Symbol {
+                index: 3,
+                name: "___stack_chk_fail".to_string(),
+            }
+        );
+
+        let got: Vec<_> = symbols
+            .iter()
+            .filter(|symbol| symbol.section == IndirectSection::NonLazySymbolPointers)
+            .collect();
+        assert_eq!(got.len(), 4);
+        assert_eq!(got[0].target, IndirectTarget::Absolute);
+
+        let lazy: Vec<_> = symbols
+            .iter()
+            .filter(|symbol| symbol.section == IndirectSection::LazySymbolPointers)
+            .collect();
+        assert_eq!(lazy.len(), 8);
+        assert_eq!(lazy[0].address, 0x100001020);
+        assert_eq!(lazy[0].target, IndirectTarget::Local);
+        assert_eq!(lazy[1].address, 0x100001028);
+        assert_eq!(lazy[1].target, IndirectTarget::LocalAbsolute);
+        assert_eq!(
+            lazy[7].target,
+            IndirectTarget::Symbol {
+                index: 0xb,
+                name: "_waitpid".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_indirect_symbol() {
+        let mut data = LAUNCHER.to_vec();
+        data[0x21e0..0x21e4].copy_from_slice(&13u32.to_le_bytes());
+        let mut r = Cursor::new(&data);
+        let macho = MachO::from_reader(&mut r).unwrap();
+        assert!(matches!(
+            macho.indirect_symbols(&mut r),
+            Err(Error::InvalidSymbolIndex(13))
+        ));
+    }
+}
@@ -0,0 +1,559 @@
+use crate::constants;
+use crate::error::Error;
+use crate::extractable;
+use crate::extractor::{Extractor, Insertable, Inserter};
+use crate::header::CpuType;
+use std::convert::TryInto;
+
+extractable!(X86ThreadState32 {
+    eax: u32,
+    ebx: u32,
+    ecx: u32,
+    edx: u32,
+    edi: u32,
+    esi: u32,
+    ebp: u32,
+    esp: u32,
+    ss: u32,
+    eflags: u32,
+    eip: u32,
+    cs: u32,
+    ds: u32,
+    es: u32,
+    fs: u32,
+    gs: u32,
+});
+
+extractable!(X86ThreadState64 {
+    rax: u64,
+    rbx: u64,
+    rcx: u64,
+    rdx: u64,
+    rdi: u64,
+    rsi: u64,
+    rbp: u64,
+    rsp: u64,
+    r8: u64,
+    r9: u64,
+    r10: u64,
+    r11: u64,
+    r12: u64,
+    r13: u64,
+    r14: u64,
+    r15: u64,
+    rip: u64,
+    rflags: u64,
+    cs: u64,
+    fs: u64,
+    gs: u64,
+});
+
+// the x87 registers are 80 bits wide, stored in the low bytes of each 16 byte slot
+extractable!(X86FloatState32 {
+    fpu_reserved: [u32; 2],
+    fpu_fcw: u16,
+    fpu_fsw: u16,
+    fpu_ftw: u8,
+    fpu_rsrv1: u8,
+    fpu_fop: u16,
+    fpu_ip: u32,
+    fpu_cs: u16,
+    fpu_rsrv2: u16,
+    fpu_dp: u32,
+    fpu_ds: u16,
+    fpu_rsrv3: u16,
+    fpu_mxcsr: u32,
+    fpu_mxcsrmask: u32,
+    fpu_stmm: [u128; 8],
+    fpu_xmm: [u128; 8],
+    fpu_rsrv4: [u8; 224],
+    fpu_reserved1: u32,
+});
+
+extractable!(X86FloatState64 {
+    fpu_reserved: [u32; 2],
+    fpu_fcw: u16,
+    fpu_fsw: u16,
+    fpu_ftw: u8,
+    fpu_rsrv1: u8,
+    fpu_fop: u16,
+    fpu_ip: u32,
+    fpu_cs: u16,
+    fpu_rsrv2: u16,
+    fpu_dp: u32,
+    fpu_ds: u16,
+    fpu_rsrv3: u16,
+    fpu_mxcsr: u32,
+    fpu_mxcsrmask: u32,
+    fpu_stmm: [u128; 8],
+    fpu_xmm: [u128; 16],
+    fpu_rsrv4: [u8; 96],
+    fpu_reserved1: u32,
+});
+
+extractable!(X86ExceptionState32 {
+    trapno: u16,
+    cpu: u16,
+    err: u32,
+    faultvaddr: u32,
+});
+
+extractable!(X86ExceptionState64 {
+    trapno: u16,
+    cpu: u16,
+    err: u32,
+    faultvaddr: u64,
+});
+
+extractable!(ArmThreadState32 {
+    r: [u32; 13],
+    sp: u32,
+    lr: u32,
+    pc: u32,
+    cpsr: u32,
+});
+
+extractable!(ArmThreadState64 {
+    x: [u64; 29],
+    fp: u64,
+    lr: u64,
+    sp: u64,
+    pc: u64,
+    cpsr: u32,
+    // pointer authentication flags on arm64e, padding otherwise
+    flags: u32,
+});
+
+extractable!(ArmExceptionState32 {
+    exception: u32,
+    fsr: u32,
+    far: u32,
+});
+
+extractable!(ArmExceptionState64 {
+    far: u64,
+    esr: u32,
+    exception: u32,
+});
+
+extractable!(ArmNeonState32 {
+    q: [u128; 16],
+    fpsr: u32,
+    fpcr: u32,
+    => pad 8
+});
+
+extractable!(ArmNeonState64 {
+    q: [u128; 32],
+    fpsr: u32,
+    fpcr: u32,
+    => pad 8
+});
+
+extractable!(PpcThreadState32 {
+    srr0: u32,
+    srr1: u32,
+    r: [u32; 32],
+    cr: u32,
+    xer: u32,
+    lr: u32,
+    ctr: u32,
+    mq: u32,
+    vrsave: u32,
+});
+
+extractable!(PpcThreadState64 {
+    srr0: u64,
+    srr1: u64,
+    r: [u64; 32],
+    cr: u32,
+    xer: u64,
+    lr: u64,
+    ctr: u64,
+    vrsave: u32,
+});
+
+#[derive(PartialEq, Debug)]
+pub enum ThreadState {
+    X86Thread32(X86ThreadState32),
+    X86Thread64(X86ThreadState64),
+    X86Float32(X86FloatState32),
+    X86Float64(X86FloatState64),
+    X86Exception32(X86ExceptionState32),
+    X86Exception64(X86ExceptionState64),
+    // x86_THREAD_STATE, x86_FLOAT_STATE and x86_EXCEPTION_STATE wrap a flavor with a header
+    X86(Box<ThreadState>),
+    ArmThread32(ArmThreadState32),
+    ArmThread64(ArmThreadState64),
+    ArmException32(ArmExceptionState32),
+    ArmException64(ArmExceptionState64),
+    ArmNeon32(ArmNeonState32),
+    ArmNeon64(ArmNeonState64),
+    PpcThread32(PpcThreadState32),
+    PpcThread64(PpcThreadState64),
+    Unknown { flavor: u32, state: Vec<u32> },
+}
+
+// the wrapping flavor for an x86 flavor
+fn x86_flavor(flavor: u32) -> Option<u32> {
+    match flavor {
+        constants::X86_THREAD_STATE32 | constants::X86_THREAD_STATE64 => {
+            Some(constants::X86_THREAD_STATE)
+        }
+        constants::X86_FLOAT_STATE32 | constants::X86_FLOAT_STATE64 => {
+            Some(constants::X86_FLOAT_STATE)
+        }
+        constants::X86_EXCEPTION_STATE32 | constants::X86_EXCEPTION_STATE64 => {
+            Some(constants::X86_EXCEPTION_STATE)
+        }
+        _ => None,
+    }
+}
+
+fn read_words(e: &mut Extractor, count: u32) -> Result<Vec<u32>, Error> {
+    let mut words = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        words.push(e.try_into()?);
+    }
+    Ok(words)
+}
+
+impl ThreadState {
+    // flavor numbers are only meaningful for a particular architecture, and states with an
+    // unexpected size are kept as raw words
+    fn parse(e: &mut Extractor, cputype: &CpuType, flavor: u32, count: u32) -> Result<Self, Error> {
+        let x86 = matches!(cputype, CpuType::X86 | CpuType::X86_64);
+        let arm = matches!(cputype, CpuType::Arm | CpuType::Arm64 | CpuType::Arm64_32);
+        let ppc = matches!(cputype, CpuType::PowerPC | CpuType::PowerPC64);
+        Ok(match (flavor, count) {
+            (constants::X86_THREAD_STATE32, constants::X86_THREAD_STATE32_COUNT) if x86 => {
+                Self::X86Thread32(e.try_into()?)
+            }
+            (constants::X86_THREAD_STATE64, constants::X86_THREAD_STATE64_COUNT) if x86 => {
+                Self::X86Thread64(e.try_into()?)
+            }
+            (constants::X86_FLOAT_STATE32, constants::X86_FLOAT_STATE32_COUNT) if x86 => {
+                Self::X86Float32(e.try_into()?)
+            }
+            (constants::X86_FLOAT_STATE64, constants::X86_FLOAT_STATE64_COUNT) if x86 => {
+                Self::X86Float64(e.try_into()?)
+            }
+            (constants::X86_EXCEPTION_STATE32, constants::X86_EXCEPTION_STATE32_COUNT) if x86 => {
+                Self::X86Exception32(e.try_into()?)
+            }
+            (constants::X86_EXCEPTION_STATE64, constants::X86_EXCEPTION_STATE64_COUNT) if x86 => {
+                Self::X86Exception64(e.try_into()?)
+            }
+            (
+                constants::X86_THREAD_STATE
+                | constants::X86_FLOAT_STATE
+                | constants::X86_EXCEPTION_STATE,
+                _,
+            ) if x86 && count >= 2 => {
+                let inner_flavor: u32 = e.try_into()?;
+                let inner_count: u32 = e.try_into()?;
+                if x86_flavor(inner_flavor) == Some(flavor) && inner_count == count - 2 {
+                    Self::X86(Box::new(Self::parse(
+                        e,
+                        cputype,
+                        inner_flavor,
+                        inner_count,
+                    )?))
+                } else {
+                    let mut state = vec![inner_flavor, inner_count];
+                    state.extend(read_words(e, count - 2)?);
+                    Self::Unknown { flavor, state }
+                }
+            }
+            (constants::ARM_THREAD_STATE, constants::ARM_THREAD_STATE_COUNT) if arm => {
+                Self::ArmThread32(e.try_into()?)
+            }
+            (constants::ARM_THREAD_STATE64, constants::ARM_THREAD_STATE64_COUNT) if arm => {
+                Self::ArmThread64(e.try_into()?)
+            }
+            (constants::ARM_EXCEPTION_STATE, constants::ARM_EXCEPTION_STATE_COUNT) if arm => {
+                Self::ArmException32(e.try_into()?)
+            }
+            (constants::ARM_EXCEPTION_STATE64, constants::ARM_EXCEPTION_STATE64_COUNT) if arm => {
+                Self::ArmException64(e.try_into()?)
+            }
+            (constants::ARM_NEON_STATE, constants::ARM_NEON_STATE_COUNT) if arm => {
+                Self::ArmNeon32(e.try_into()?)
+            }
+            (constants::ARM_NEON_STATE64, constants::ARM_NEON_STATE64_COUNT) if arm => {
+                Self::ArmNeon64(e.try_into()?)
+            }
+            (constants::PPC_THREAD_STATE, constants::PPC_THREAD_STATE_COUNT) if ppc => {
+                Self::PpcThread32(e.try_into()?)
+            }
+            (constants::PPC_THREAD_STATE64, constants::PPC_THREAD_STATE64_COUNT) if ppc => {
+                Self::PpcThread64(e.try_into()?)
+            }
+            _ => Self::Unknown {
+                flavor,
+                state: read_words(e, count)?,
+            },
+        })
+    }
+
+    pub fn flavor(&self) -> u32 {
+        match self {
+            Self::X86Thread32(_) => constants::X86_THREAD_STATE32,
+            Self::X86Thread64(_) => constants::X86_THREAD_STATE64,
+            Self::X86Float32(_) => constants::X86_FLOAT_STATE32,
+            Self::X86Float64(_) => constants::X86_FLOAT_STATE64,
+            Self::X86Exception32(_) => constants::X86_EXCEPTION_STATE32,
+            Self::X86Exception64(_) => constants::X86_EXCEPTION_STATE64,
+            Self::X86(state) => x86_flavor(state.flavor()).unwrap_or(constants::X86_THREAD_STATE),
+            Self::ArmThread32(_) => constants::ARM_THREAD_STATE,
+            Self::ArmThread64(_) => constants::ARM_THREAD_STATE64,
+            Self::ArmException32(_) => constants::ARM_EXCEPTION_STATE,
+            Self::ArmException64(_) => constants::ARM_EXCEPTION_STATE64,
+            Self::ArmNeon32(_) => constants::ARM_NEON_STATE,
+            Self::ArmNeon64(_) => constants::ARM_NEON_STATE64,
+            Self::PpcThread32(_) => constants::PPC_THREAD_STATE,
+            Self::PpcThread64(_) => constants::PPC_THREAD_STATE64,
+            Self::Unknown { flavor, .. } => *flavor,
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        match self {
+            Self::X86Thread32(_) => constants::X86_THREAD_STATE32_COUNT,
+            Self::X86Thread64(_) => constants::X86_THREAD_STATE64_COUNT,
+            Self::X86Float32(_) => constants::X86_FLOAT_STATE32_COUNT,
+            Self::X86Float64(_) => constants::X86_FLOAT_STATE64_COUNT,
+            Self::X86Exception32(_) => constants::X86_EXCEPTION_STATE32_COUNT,
+            Self::X86Exception64(_) => constants::X86_EXCEPTION_STATE64_COUNT,
+            Self::X86(state) => state.count() + 2,
+            Self::ArmThread32(_) => constants::ARM_THREAD_STATE_COUNT,
+            Self::ArmThread64(_) => constants::ARM_THREAD_STATE64_COUNT,
+            Self::ArmException32(_) => constants::ARM_EXCEPTION_STATE_COUNT,
+            Self::ArmException64(_) => constants::ARM_EXCEPTION_STATE64_COUNT,
+            Self::ArmNeon32(_) => constants::ARM_NEON_STATE_COUNT,
+            Self::ArmNeon64(_) => constants::ARM_NEON_STATE64_COUNT,
+            Self::PpcThread32(_) => constants::PPC_THREAD_STATE_COUNT,
+            Self::PpcThread64(_) => constants::PPC_THREAD_STATE64_COUNT,
+            Self::Unknown { state, .. } => state.len() as u32,
+        }
+    }
+
+    fn insert(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.flavor().insert_into(i)?;
+        self.count().insert_into(i)?;
+        match self {
+            Self::X86Thread32(state) => state.insert_into(i),
+            Self::X86Thread64(state) => state.insert_into(i),
+            Self::X86Float32(state) => state.insert_into(i),
+            Self::X86Float64(state) => state.insert_into(i),
+            Self::X86Exception32(state) => state.insert_into(i),
+            Self::X86Exception64(state) => state.insert_into(i),
+            Self::X86(state) => state.insert(i),
+            Self::ArmThread32(state) => state.insert_into(i),
+            Self::ArmThread64(state) => state.insert_into(i),
+            Self::ArmException32(state) => state.insert_into(i),
+            Self::ArmException64(state) => state.insert_into(i),
+            Self::ArmNeon32(state) => state.insert_into(i),
+            Self::ArmNeon64(state) => state.insert_into(i),
+            Self::PpcThread32(state) => state.insert_into(i),
+            Self::PpcThread64(state) => state.insert_into(i),
+            Self::Unknown { state, .. } => {
+                for word in state {
+                    word.insert_into(i)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    pub fn program_counter(&self) -> Option<u64> {
+        match self {
+            Self::X86Thread32(state) => Some(state.eip as u64),
+            Self::X86Thread64(state) => Some(state.rip),
+            Self::X86(state) => state.program_counter(),
+            Self::ArmThread32(state) => Some(state.pc as u64),
+            Self::ArmThread64(state) => Some(state.pc),
+            Self::PpcThread32(state) => Some(state.srr0 as u64),
+            Self::PpcThread64(state) => Some(state.srr0),
+            _ => None,
+        }
+    }
+}
+
+#[derive(PartialEq, Debug)]
+pub struct ThreadCommand {
+    pub states: Vec<ThreadState>,
+    pub padding: Vec<u8>,
+}
+
+impl ThreadCommand {
+    pub(crate) fn parse(e: &mut Extractor, size: u32, cputype: &CpuType) -> Result<Self, Error> {
+        let mut states = Vec::new();
+        let mut remaining = size - 8;
+        while remaining >= 8 {
+            let flavor: u32 = e.try_into()?;
+            let count: u32 = e.try_into()?;
+            if count as u64 * 4 > (remaining - 8) as u64 {
+                return Err(Error::InvalidThreadState { flavor, count });
+            }
+            states.push(ThreadState::parse(e, cputype, flavor, count)?);
+            remaining -= 8 + count * 4;
+        }
+        Ok(Self {
+            states,
+            padding: Vec::new(),
+        })
+    }
+
+    pub(crate) fn insert(&self, i: &mut Inserter) -> Result<(), Error> {
+        for state in &self.states {
+            state.insert(i)?;
+        }
+        Ok(())
+    }
+
+    pub fn program_counter(&self) -> Option<u64> {
+        self.states.iter().find_map(ThreadState::program_counter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn state(flavor: u32, words: &[u32]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&flavor.to_le_bytes());
+        data.extend_from_slice(&(words.len() as u32).to_le_bytes());
+        for word in words {
+            data.extend_from_slice(&word.to_le_bytes());
+        }
+        data
+    }
+
+    fn parse(cputype: CpuType, data: &[u8]) -> Result<ThreadCommand, Error> {
+        let mut cursor = Cursor::new(data);
+        let mut e = Extractor::little_endian(&mut cursor);
+        ThreadCommand::parse(&mut e, data.len() as u32 + 8, &cputype)
+    }
+
+    fn round_trip(cputype: CpuType, data: &[u8]) -> ThreadCommand {
+        let command = parse(cputype, data).unwrap();
+        let mut written = Cursor::new(Vec::new());
+        command
+            .insert(&mut Inserter::little_endian(&mut written))
+            .unwrap();
+        assert_eq!(written.into_inner(), data);
+        command
+    }
+
+    #[test]
+    fn program_counters() {
+        let mut words = vec![0; constants::X86_THREAD_STATE32_COUNT as usize];
+        words[10] = 0x1f00;
+        let command = round_trip(CpuType::X86, &state(constants::X86_THREAD_STATE32, &words));
+        assert!(matches!(command.states[0], ThreadState::X86Thread32(_)));
+        assert_eq!(command.program_counter(), Some(0x1f00));
+
+        let mut words = vec![0; constants::X86_THREAD_STATE64_COUNT as usize];
+        words[32] = 0x1f00;
+        words[33] = 1;
+        let command = round_trip(
+            CpuType::X86_64,
+            &state(constants::X86_THREAD_STATE64, &words),
+        );
+        assert!(matches!(command.states[0], ThreadState::X86Thread64(_)));
+        assert_eq!(command.program_counter(), Some(0x1_0000_1f00));
+
+        let mut words = vec![0; constants::ARM_THREAD_STATE_COUNT as usize];
+        words[15] = 0x2f00;
+        let command = round_trip(CpuType::Arm, &state(constants::ARM_THREAD_STATE, &words));
+        assert!(matches!(command.states[0], ThreadState::ArmThread32(_)));
+        assert_eq!(command.program_counter(), Some(0x2f00));
+
+        let mut words = vec![0; constants::ARM_THREAD_STATE64_COUNT as usize];
+        words[64] = 0x2f00;
+        words[65] = 1;
+        let command = round_trip(
+            CpuType::Arm64,
+            &state(constants::ARM_THREAD_STATE64, &words),
+        );
+        assert!(matches!(command.states[0], ThreadState::ArmThread64(_)));
+        assert_eq!(command.program_counter(), Some(0x1_0000_2f00));
+    }
+
+    #[test]
+    fn wrapped_x86_state() {
+        let mut words = vec![
+            constants::X86_THREAD_STATE64,
+            constants::X86_THREAD_STATE64_COUNT,
+        ];
+        words.resize(constants::X86_THREAD_STATE64_COUNT as usize + 2, 0);
+        words[2 + 32] = 0x3f00;
+        let data = [
+            state(constants::X86_EXCEPTION_STATE64, &[0; 4]),
+            state(constants::X86_THREAD_STATE, &words),
+        ]
+        .concat();
+        let command = round_trip(CpuType::X86_64, &data);
+        assert!(matches!(command.states[0], ThreadState::X86Exception64(_)));
+        match &command.states[1] {
+            ThreadState::X86(state) => assert!(matches!(**state, ThreadState::X86Thread64(_))),
+            state => panic!("expected a wrapped state, got {:?}", state),
+        }
+        assert_eq!(command.states[1].flavor(), constants::X86_THREAD_STATE);
+        assert_eq!(command.states[1].count(), words.len() as u32);
+        assert_eq!(command.program_counter(), Some(0x3f00));
+
+        // the header doesn't describe the rest of the state
+        words[1] = constants::X86_THREAD_STATE32_COUNT;
+        let command = round_trip(CpuType::X86_64, &state(constants::X86_THREAD_STATE, &words));
+        assert_eq!(
+            command.states,
+            [ThreadState::Unknown {
+                flavor: constants::X86_THREAD_STATE,
+                state: words,
+            }]
+        );
+        assert_eq!(command.program_counter(), None);
+    }
+
+    #[test]
+    fn unexpected_sizes() {
+        // the right flavor with the wrong count, and an arm flavor on x86
+        let data = [
+            state(constants::X86_THREAD_STATE64, &[1, 2, 3]),
+            state(constants::ARM_NEON_STATE64, &[4]),
+        ]
+        .concat();
+        let command = round_trip(CpuType::X86_64, &data);
+        assert_eq!(
+            command.states,
+            [
+                ThreadState::Unknown {
+                    flavor: constants::X86_THREAD_STATE64,
+                    state: vec![1, 2, 3],
+                },
+                ThreadState::Unknown {
+                    flavor: constants::ARM_NEON_STATE64,
+                    state: vec![4],
+                },
+            ]
+        );
+        assert_eq!(command.program_counter(), None);
+    }
+
+    #[test]
+    fn invalid_thread_state() {
+        let mut data = state(constants::ARM_THREAD_STATE64, &[0; 4]);
+        data[4..8].copy_from_slice(&constants::ARM_THREAD_STATE64_COUNT.to_le_bytes());
+        assert!(matches!(
+            parse(CpuType::Arm64, &data),
+            Err(Error::InvalidThreadState {
+                flavor: constants::ARM_THREAD_STATE64,
+                count: constants::ARM_THREAD_STATE64_COUNT,
+            })
+        ));
+    }
+}
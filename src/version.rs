@@ -0,0 +1,475 @@
+use crate::command::{BuildToolVersion, BuildVersionCommand, LoadCommand, VersionMinCommand};
+use crate::constants;
+use crate::error::Error;
+use crate::extractor::{Extractor, Insertable, Inserter};
+use crate::macho::MachO;
+use std::convert::{TryFrom, TryInto};
+
+// a version packed as xxxx.yy.zz
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct Version(pub u32);
+
+impl Version {
+    pub fn new(major: u16, minor: u8, patch: u8) -> Self {
+        Self(((major as u32) << 16) | ((minor as u32) << 8) | patch as u32)
+    }
+
+    pub fn major(self) -> u16 {
+        (self.0 >> 16) as u16
+    }
+
+    pub fn minor(self) -> u8 {
+        (self.0 >> 8) as u8
+    }
+
+    pub fn patch(self) -> u8 {
+        self.0 as u8
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major(), self.minor(), self.patch())
+    }
+}
+
+impl TryFrom<&mut Extractor<'_>> for Version {
+    type Error = Error;
+
+    fn try_from(e: &mut Extractor) -> Result<Self, Self::Error> {
+        Ok(Version(e.try_into()?))
+    }
+}
+
+impl Insertable for Version {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.0.insert_into(i)
+    }
+}
+
+// a source version packed as a24.b10.c10.d10.e10
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+pub struct SourceVersion(pub u64);
+
+impl SourceVersion {
+    pub fn components(self) -> [u64; 5] {
+        [
+            self.0 >> 40,
+            (self.0 >> 30) & 0x3ff,
+            (self.0 >> 20) & 0x3ff,
+            (self.0 >> 10) & 0x3ff,
+            self.0 & 0x3ff,
+        ]
+    }
+}
+
+impl std::fmt::Display for SourceVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let [a, b, c, d, e] = self.components();
+        write!(f, "{}.{}.{}.{}.{}", a, b, c, d, e)
+    }
+}
+
+impl TryFrom<&mut Extractor<'_>> for SourceVersion {
+    type Error = Error;
+
+    fn try_from(e: &mut Extractor) -> Result<Self, Self::Error> {
+        Ok(SourceVersion(e.try_into()?))
+    }
+}
+
+impl Insertable for SourceVersion {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.0.insert_into(i)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Platform {
+    MacOS,
+    IOS,
+    TvOS,
+    WatchOS,
+    BridgeOS,
+    MacCatalyst,
+    IOSSimulator,
+    TvOSSimulator,
+    WatchOSSimulator,
+    DriverKit,
+    VisionOS,
+    VisionOSSimulator,
+    Unknown(u32),
+}
+
+impl Platform {
+    pub(crate) fn from_u32(v: u32) -> Self {
+        match v {
+            constants::PLATFORM_MACOS => Self::MacOS,
+            constants::PLATFORM_IOS => Self::IOS,
+            constants::PLATFORM_TVOS => Self::TvOS,
+            constants::PLATFORM_WATCHOS => Self::WatchOS,
+            constants::PLATFORM_BRIDGEOS => Self::BridgeOS,
+            constants::PLATFORM_MACCATALYST => Self::MacCatalyst,
+            constants::PLATFORM_IOSSIMULATOR => Self::IOSSimulator,
+            constants::PLATFORM_TVOSSIMULATOR => Self::TvOSSimulator,
+            constants::PLATFORM_WATCHOSSIMULATOR => Self::WatchOSSimulator,
+            constants::PLATFORM_DRIVERKIT => Self::DriverKit,
+            constants::PLATFORM_VISIONOS => Self::VisionOS,
+            constants::PLATFORM_VISIONOSSIMULATOR => Self::VisionOSSimulator,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        match self {
+            Self::MacOS => constants::PLATFORM_MACOS,
+            Self::IOS => constants::PLATFORM_IOS,
+            Self::TvOS => constants::PLATFORM_TVOS,
+            Self::WatchOS => constants::PLATFORM_WATCHOS,
+            Self::BridgeOS => constants::PLATFORM_BRIDGEOS,
+            Self::MacCatalyst => constants::PLATFORM_MACCATALYST,
+            Self::IOSSimulator => constants::PLATFORM_IOSSIMULATOR,
+            Self::TvOSSimulator => constants::PLATFORM_TVOSSIMULATOR,
+            Self::WatchOSSimulator => constants::PLATFORM_WATCHOSSIMULATOR,
+            Self::DriverKit => constants::PLATFORM_DRIVERKIT,
+            Self::VisionOS => constants::PLATFORM_VISIONOS,
+            Self::VisionOSSimulator => constants::PLATFORM_VISIONOSSIMULATOR,
+            Self::Unknown(value) => value,
+        }
+    }
+}
+
+impl std::fmt::Display for Platform {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::MacOS => write!(f, "macOS"),
+            Self::IOS => write!(f, "iOS"),
+            Self::TvOS => write!(f, "tvOS"),
+            Self::WatchOS => write!(f, "watchOS"),
+            Self::BridgeOS => write!(f, "bridgeOS"),
+            Self::MacCatalyst => write!(f, "Mac Catalyst"),
+            Self::IOSSimulator => write!(f, "iOS Simulator"),
+            Self::TvOSSimulator => write!(f, "tvOS Simulator"),
+            Self::WatchOSSimulator => write!(f, "watchOS Simulator"),
+            Self::DriverKit => write!(f, "DriverKit"),
+            Self::VisionOS => write!(f, "visionOS"),
+            Self::VisionOSSimulator => write!(f, "visionOS Simulator"),
+            Self::Unknown(value) => write!(f, "unknown platform {}", value),
+        }
+    }
+}
+
+impl TryFrom<&mut Extractor<'_>> for Platform {
+    type Error = Error;
+
+    fn try_from(e: &mut Extractor) -> Result<Self, Self::Error> {
+        Ok(Platform::from_u32(e.try_into()?))
+    }
+}
+
+impl Insertable for Platform {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.to_u32().insert_into(i)
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Tool {
+    Clang,
+    Swift,
+    Ld,
+    Unknown(u32),
+}
+
+impl Tool {
+    fn from_u32(v: u32) -> Self {
+        match v {
+            constants::TOOL_CLANG => Self::Clang,
+            constants::TOOL_SWIFT => Self::Swift,
+            constants::TOOL_LD => Self::Ld,
+            unknown => Self::Unknown(unknown),
+        }
+    }
+
+    fn to_u32(self) -> u32 {
+        match self {
+            Self::Clang => constants::TOOL_CLANG,
+            Self::Swift => constants::TOOL_SWIFT,
+            Self::Ld => constants::TOOL_LD,
+            Self::Unknown(value) => value,
+        }
+    }
+}
+
+impl TryFrom<&mut Extractor<'_>> for Tool {
+    type Error = Error;
+
+    fn try_from(e: &mut Extractor) -> Result<Self, Self::Error> {
+        Ok(Tool::from_u32(e.try_into()?))
+    }
+}
+
+impl Insertable for Tool {
+    fn insert_into(&self, i: &mut Inserter) -> Result<(), Error> {
+        self.to_u32().insert_into(i)
+    }
+}
+
+// version-min commands predate simulator platforms, which share the device command
+fn version_min_cmd(platform: Platform) -> Option<u32> {
+    match platform {
+        Platform::MacOS => Some(constants::LC_VERSION_MIN_MACOSX),
+        Platform::IOS | Platform::IOSSimulator => Some(constants::LC_VERSION_MIN_IPHONEOS),
+        Platform::TvOS | Platform::TvOSSimulator => Some(constants::LC_VERSION_MIN_TVOS),
+        Platform::WatchOS | Platform::WatchOSSimulator => Some(constants::LC_VERSION_MIN_WATCHOS),
+        _ => None,
+    }
+}
+
+// the platform named by a version command; version-min commands only name device platforms
+fn version_platform(command: &LoadCommand) -> Option<Platform> {
+    match command {
+        LoadCommand::BuildVersion(build) => Some(build.platform),
+        LoadCommand::VersionMinMacosx(_) => Some(Platform::MacOS),
+        LoadCommand::VersionMinIphoneos(_) => Some(Platform::IOS),
+        LoadCommand::VersionMinTvos(_) => Some(Platform::TvOS),
+        LoadCommand::VersionMinWatchos(_) => Some(Platform::WatchOS),
+        _ => None,
+    }
+}
+
+impl MachO {
+    // replaces the version command in place, converting between forms. A lone version command
+    // is replaced whatever its platform, so a wrong platform can be corrected; binaries built for
+    // several platforms replace the command for this platform, or append one if there is none.
+    fn put_version_command(
+        &mut self,
+        platform: Platform,
+        command: LoadCommand,
+    ) -> Result<(), Error> {
+        let size = command.size(&self.header)?;
+        let versions: Vec<usize> = self
+            .commands
+            .iter()
+            .enumerate()
+            .filter(|(_, existing)| version_platform(existing).is_some())
+            .map(|(index, _)| index)
+            .collect();
+        let index = match versions.as_slice() {
+            [index] => Some(*index),
+            _ => versions
+                .into_iter()
+                .find(|index| version_platform(&self.commands[*index]) == Some(platform)),
+        };
+        let existing_size = match index {
+            Some(index) => self.commands[index].size(&self.header)?,
+            None => 0,
+        };
+        if size > existing_size {
+            let required = (size - existing_size) as u64;
+            let available = self.header_padding();
+            if required > available {
+                return Err(Error::InsufficientHeaderPadding {
+                    required,
+                    available,
+                });
+            }
+        }
+        self.header.sizeofcmds = self.header.sizeofcmds - existing_size + size;
+        match index {
+            Some(index) => self.commands[index] = command,
+            None => {
+                self.header.ncmds += 1;
+                self.commands.push(command);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn set_build_version(
+        &mut self,
+        platform: Platform,
+        minos: Version,
+        sdk: Version,
+        tools: Vec<BuildToolVersion>,
+    ) -> Result<(), Error> {
+        let command = LoadCommand::BuildVersion(BuildVersionCommand {
+            platform,
+            minos,
+            sdk,
+            ntools: tools.len() as u32,
+            tools,
+            padding: Vec::new(),
+        });
+        self.put_version_command(platform, command)
+    }
+
+    pub fn set_version_min(
+        &mut self,
+        platform: Platform,
+        version: Version,
+        sdk: Version,
+    ) -> Result<(), Error> {
+        let command = VersionMinCommand {
+            version,
+            sdk,
+            padding: Vec::new(),
+        };
+        let command = match version_min_cmd(platform) {
+            Some(constants::LC_VERSION_MIN_MACOSX) => LoadCommand::VersionMinMacosx(command),
+            Some(constants::LC_VERSION_MIN_IPHONEOS) => LoadCommand::VersionMinIphoneos(command),
+            Some(constants::LC_VERSION_MIN_TVOS) => LoadCommand::VersionMinTvos(command),
+            Some(constants::LC_VERSION_MIN_WATCHOS) => LoadCommand::VersionMinWatchos(command),
+            _ => return Err(Error::UnsupportedVersionMinPlatform(platform.to_u32())),
+        };
+        self.put_version_command(platform, command)
+    }
+
+    // returns whether any command was removed
+    pub fn remove_build_version(&mut self, platform: Platform) -> Result<bool, Error> {
+        let mut removed = Vec::new();
+        let mut kept = Vec::new();
+        for command in self.commands.drain(..) {
+            if version_platform(&command) == Some(platform) {
+                removed.push(command);
+            } else {
+                kept.push(command);
+            }
+        }
+        self.commands = kept;
+        for command in &removed {
+            self.header.ncmds -= 1;
+            self.header.sizeofcmds -= command.size(&self.header)?;
+        }
+        Ok(!removed.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn launcher() -> MachO {
+        let data = include_bytes!("../tests/fixtures/osx_launcher_x86_64");
+        MachO::from_reader(&mut Cursor::new(&data[..])).unwrap()
+    }
+
+    fn versions(macho: &MachO) -> Vec<(u32, Platform)> {
+        macho
+            .commands
+            .iter()
+            .filter_map(|command| Some((command.cmd(), version_platform(command)?)))
+            .collect()
+    }
+
+    fn assert_sizes(macho: &MachO) {
+        let mut sizeofcmds = 0;
+        for command in &macho.commands {
+            sizeofcmds += command.size(&macho.header).unwrap();
+        }
+        assert_eq!(macho.header.ncmds as usize, macho.commands.len());
+        assert_eq!(macho.header.sizeofcmds, sizeofcmds);
+    }
+
+    #[test]
+    fn replaces_lone_version_command() {
+        let mut macho = launcher();
+        let version = Version::new(14, 0, 0);
+        macho
+            .set_build_version(Platform::IOS, version, version, Vec::new())
+            .unwrap();
+        assert_eq!(
+            versions(&macho),
+            [(constants::LC_BUILD_VERSION, Platform::IOS)]
+        );
+        macho
+            .set_build_version(Platform::IOSSimulator, version, version, Vec::new())
+            .unwrap();
+        assert_eq!(
+            versions(&macho),
+            [(constants::LC_BUILD_VERSION, Platform::IOSSimulator)]
+        );
+        macho
+            .set_version_min(Platform::IOS, version, version)
+            .unwrap();
+        assert_eq!(
+            versions(&macho),
+            [(constants::LC_VERSION_MIN_IPHONEOS, Platform::IOS)]
+        );
+        assert_sizes(&macho);
+    }
+
+    #[test]
+    fn replaces_matching_platform_when_zippered() {
+        let mut macho = launcher();
+        let version = Version::new(14, 0, 0);
+        macho
+            .set_build_version(Platform::MacOS, version, version, Vec::new())
+            .unwrap();
+        let catalyst = LoadCommand::BuildVersion(BuildVersionCommand {
+            platform: Platform::MacCatalyst,
+            minos: version,
+            sdk: version,
+            ntools: 0,
+            tools: Vec::new(),
+            padding: Vec::new(),
+        });
+        macho.header.ncmds += 1;
+        macho.header.sizeofcmds += catalyst.size(&macho.header).unwrap();
+        macho.commands.push(catalyst);
+
+        let newer = Version::new(15, 0, 0);
+        macho
+            .set_build_version(Platform::MacCatalyst, newer, newer, Vec::new())
+            .unwrap();
+        let minos: Vec<_> = macho
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                LoadCommand::BuildVersion(build) => Some((build.platform, build.minos)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            minos,
+            [(Platform::MacOS, version), (Platform::MacCatalyst, newer)]
+        );
+        assert_sizes(&macho);
+    }
+
+    #[test]
+    fn removes_only_matching_platform() {
+        let mut macho = launcher();
+        let version = Version::new(14, 0, 0);
+        macho
+            .set_version_min(Platform::IOS, version, version)
+            .unwrap();
+        assert!(!macho.remove_build_version(Platform::IOSSimulator).unwrap());
+        assert_eq!(
+            versions(&macho),
+            [(constants::LC_VERSION_MIN_IPHONEOS, Platform::IOS)]
+        );
+        assert!(macho.remove_build_version(Platform::IOS).unwrap());
+        assert!(versions(&macho).is_empty());
+        assert_sizes(&macho);
+    }
+
+    #[test]
+    fn keeps_sizes_of_padded_commands() {
+        let mut macho = launcher();
+        for command in &mut macho.commands {
+            if let LoadCommand::VersionMinMacosx(command) = command {
+                command.padding = vec![0; 8];
+                macho.header.sizeofcmds += 8;
+            }
+        }
+        let version = Version::new(14, 0, 0);
+        macho
+            .set_build_version(Platform::MacOS, version, version, Vec::new())
+            .unwrap();
+        assert_sizes(&macho);
+        assert!(macho.remove_build_version(Platform::MacOS).unwrap());
+        assert_sizes(&macho);
+    }
+}
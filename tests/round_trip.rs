@@ -0,0 +1,61 @@
+use mach_o_man::MachO;
+use std::io::Cursor;
+
+fn round_trip(data: &[u8]) {
+    let macho = MachO::from_reader(&mut Cursor::new(data)).unwrap();
+    let commands_end = (macho.header.size() + macho.header.sizeofcmds) as usize;
+
+    let mut written = Cursor::new(Vec::new());
+    macho.write_to(&mut written).unwrap();
+    let written = written.into_inner();
+    assert_eq!(written, &data[..commands_end]);
+
+    let reparsed = MachO::from_reader(&mut Cursor::new(written)).unwrap();
+    assert_eq!(reparsed, macho);
+}
+
+#[test]
+fn round_trip_32_bit() {
+    round_trip(include_bytes!("fixtures/crcmod_i386.so"));
+}
+
+#[test]
+fn round_trip_64_bit() {
+    round_trip(include_bytes!("fixtures/osx_launcher_x86_64"));
+}
+
+#[test]
+fn round_trip_padded_commands() {
+    let mut commands = Vec::new();
+    // LC_UUID with eight bytes of trailing garbage
+    commands.extend_from_slice(&0x1bu32.to_le_bytes());
+    commands.extend_from_slice(&32u32.to_le_bytes());
+    commands.extend_from_slice(&[0x11; 16]);
+    commands.extend_from_slice(&[0xff; 8]);
+    // LC_LOAD_DYLINKER padded well past the string
+    commands.extend_from_slice(&0xeu32.to_le_bytes());
+    commands.extend_from_slice(&40u32.to_le_bytes());
+    commands.extend_from_slice(&12u32.to_le_bytes());
+    commands.extend_from_slice(b"/usr/lib/dyld\0");
+    commands.extend_from_slice(&[0xaa; 14]);
+
+    let mut data = Vec::new();
+    for word in &[
+        0xfeedfacfu32,
+        0x01000007,
+        3,
+        2,
+        2,
+        commands.len() as u32,
+        0,
+        0x1234,
+    ] {
+        data.extend_from_slice(&word.to_le_bytes());
+    }
+    data.extend_from_slice(&commands);
+    round_trip(&data);
+
+    let macho = MachO::from_reader(&mut Cursor::new(&data)).unwrap();
+    assert_eq!(macho.header.reserved, 0x1234);
+    assert_eq!(macho.dylinker(), Some("/usr/lib/dyld"));
+}